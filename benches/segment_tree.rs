@@ -0,0 +1,70 @@
+//! Compares the recursive `SegmentTree` with `IterativeSegmentTree`
+//! on a typical Codeforces-sized workload. Run with `cargo bench`.
+
+use std::hint::black_box;
+
+use cforces_rs::ds::{
+    iterative_segment_tree::IterativeSegmentTree, monoid::Sum, segment_tree::SegmentTree,
+};
+use criterion::{Criterion, criterion_group, criterion_main};
+
+type Int = i64;
+
+const N: usize = 200_000;
+const QUERIES: usize = 200_000;
+
+/// Pseudo-random `(idx, l, r, value)` queries, generated with a simple LCG.
+fn queries() -> Vec<(usize, usize, usize, Int)> {
+    let mut state: u64 = 1_415_811;
+    let mut next = || {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        usize::try_from(state >> 33).unwrap()
+    };
+    (0..QUERIES)
+        .map(|_| {
+            let (i, j) = (next() % (N + 1), next() % (N + 1));
+            (
+                next() % N,
+                i.min(j),
+                i.max(j),
+                Int::try_from(next() % 1000).unwrap(),
+            )
+        })
+        .collect()
+}
+
+fn bench_segment_trees(c: &mut Criterion) {
+    let arr: Vec<Int> = (0..N).map(|i| Int::try_from(i % 1000).unwrap()).collect();
+    let queries = queries();
+
+    let mut group = c.benchmark_group("segment_tree_sum");
+    group.sample_size(10);
+    group.bench_function("recursive", |b| {
+        b.iter(|| {
+            let mut st = SegmentTree::<Sum<Int>>::from_slice(&arr);
+            let mut total: Int = 0;
+            for &(idx, l, r, value) in &queries {
+                st.update(idx, value);
+                total += st.compute(l..r);
+            }
+            black_box(total)
+        });
+    });
+    group.bench_function("iterative", |b| {
+        b.iter(|| {
+            let mut st = IterativeSegmentTree::<Sum<Int>>::from_slice(&arr);
+            let mut total: Int = 0;
+            for &(idx, l, r, value) in &queries {
+                st.update(idx, value);
+                total += st.compute(l..r);
+            }
+            black_box(total)
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_segment_trees);
+criterion_main!(benches);
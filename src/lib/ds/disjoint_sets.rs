@@ -1,7 +1,21 @@
+use std::{
+    cmp::{max, min},
+    ops::Range,
+};
+
+use super::{
+    monoid::{Group, Xor},
+    segment_tree::Node,
+};
+
 /// Represents a union of disjoint sets. Each set's elements are arranged in a
-/// tree, whose root is the set's representative.
+/// tree, whose root is the set's representative. Smaller trees are attached
+/// under larger ones (union by size).
 pub struct DisjointSets {
     parent: Vec<usize>,
+    /// Size of the set, valid only for representatives.
+    size: Vec<usize>,
+    components: usize,
 }
 
 impl DisjointSets {
@@ -10,6 +24,8 @@ impl DisjointSets {
     pub fn new(size: usize) -> Self {
         Self {
             parent: (0..size).collect(),
+            size: vec![1; size],
+            components: size,
         }
     }
 
@@ -30,20 +46,324 @@ impl DisjointSets {
     /// Merges the sets containing u and v into a single set containing their
     /// union. Returns true if u and v were previously in different sets.
     pub fn merge(&mut self, u: usize, v: usize) -> bool {
-        let (pu, pv) = (self.leader(u), self.leader(v));
+        let (mut pu, mut pv) = (self.leader(u), self.leader(v));
+        if pu == pv {
+            return false;
+        }
+        if self.size[pu] > self.size[pv] {
+            std::mem::swap(&mut pu, &mut pv);
+        }
         self.parent[pu] = pv;
-        pu != pv
+        self.size[pv] += self.size[pu];
+        self.components -= 1;
+        true
     }
 
     pub fn same(&mut self, u: usize, v: usize) -> bool {
         self.leader(u) == self.leader(v)
     }
+
+    /// Returns the size of the set containing u.
+    pub fn size(&mut self, u: usize) -> usize {
+        let pu = self.leader(u);
+        self.size[pu]
+    }
+
+    /// Returns the number of disjoint sets.
+    #[must_use]
+    pub fn count_components(&self) -> usize {
+        self.components
+    }
+
+    /// Returns all sets, each in ascending order. Sets are ordered by their
+    /// smallest element.
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        let n = self.parent.len();
+        let mut group_id = vec![None; n];
+        let mut groups: Vec<Vec<usize>> = Vec::with_capacity(self.components);
+        for u in 0..n {
+            let pu = self.leader(u);
+            let id = *group_id[pu].get_or_insert_with(|| {
+                groups.push(Vec::with_capacity(self.size[pu]));
+                groups.len() - 1
+            });
+            groups[id].push(u);
+        }
+        groups
+    }
+}
+
+/// Error returned when a new constraint contradicts the known ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contradiction;
+
+/// Disjoint sets, which also keep track of the differences `x_v - x_u` of
+/// unknown values `x` within each set. Differences are elements of the abelian
+/// group `G`, e.g. `Sum<i64>` for integers, `Xor<bool>` for parities.
+pub struct PotentialDisjointSets<G: Group> {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    /// `potential[u] = x_u - x_parent[u]`.
+    potential: Vec<G::Value>,
+}
+
+/// Tracks parities of the elements, e.g. colors in a bipartite graph:
+/// `merge(u, v, true)` means that `u` and `v` have different parities.
+pub type ParityDisjointSets = PotentialDisjointSets<Xor<bool>>;
+
+impl<G: Group> PotentialDisjointSets<G>
+where
+    G::Value: PartialEq,
+{
+    /// Initializes disjoint sets containing one element each.
+    #[must_use]
+    pub fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+            size: vec![1; size],
+            potential: vec![G::identity(); size],
+        }
+    }
+
+    /// Finds the set's representative. Does path compression along the way.
+    /// Union by size keeps the recursion depth at `O(log n)`.
+    pub fn leader(&mut self, u: usize) -> usize {
+        let p = self.parent[u];
+        if p == u {
+            return u;
+        }
+        let root = self.leader(p);
+        self.potential[u] = G::op(&self.potential[u], &self.potential[p]);
+        self.parent[u] = root;
+        root
+    }
+
+    /// Adds the constraint `x_v - x_u = w`. Returns true if u and v were
+    /// previously in different sets, or `Contradiction` if the constraint
+    /// contradicts the known ones, in which case nothing is changed.
+    ///
+    /// # Errors
+    /// Returns `Contradiction` if `diff(u, v)` is known and not equal to `w`.
+    pub fn merge(
+        &mut self,
+        u: usize,
+        v: usize,
+        w: &G::Value,
+    ) -> Result<bool, Contradiction> {
+        let (mut pu, mut pv) = (self.leader(u), self.leader(v));
+        // x_pv - x_pu
+        let mut w = G::op(
+            &G::op(w, &self.potential[u]),
+            &G::inverse(&self.potential[v]),
+        );
+        if pu == pv {
+            return if w == G::identity() {
+                Ok(false)
+            } else {
+                Err(Contradiction)
+            };
+        }
+        if self.size[pu] > self.size[pv] {
+            std::mem::swap(&mut pu, &mut pv);
+            w = G::inverse(&w);
+        }
+        self.parent[pu] = pv;
+        self.size[pv] += self.size[pu];
+        self.potential[pu] = G::inverse(&w);
+        Ok(true)
+    }
+
+    /// Returns `x_v - x_u`, if u and v are in the same set.
+    pub fn diff(&mut self, u: usize, v: usize) -> Option<G::Value> {
+        if self.leader(u) != self.leader(v) {
+            return None;
+        }
+        Some(G::op(&self.potential[v], &G::inverse(&self.potential[u])))
+    }
+
+    pub fn same(&mut self, u: usize, v: usize) -> bool {
+        self.leader(u) == self.leader(v)
+    }
+}
+
+/// Disjoint sets without path compression, which can undo merges.
+/// Union by size keeps `leader` at `O(log n)`.
+pub struct RollbackDisjointSets {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+    /// Representatives `(pu, pv)` of the successful merges, where `pu` was
+    /// attached under `pv`.
+    history: Vec<(usize, usize)>,
+}
+
+impl RollbackDisjointSets {
+    /// Initializes disjoint sets containing one element each.
+    #[must_use]
+    pub fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+            size: vec![1; size],
+            components: size,
+            history: Vec::new(),
+        }
+    }
+
+    /// Finds the set's representative.
+    #[must_use]
+    pub fn leader(&self, mut u: usize) -> usize {
+        while self.parent[u] != u {
+            u = self.parent[u];
+        }
+        u
+    }
+
+    /// Merges the sets containing u and v into a single set containing their
+    /// union. Returns true if u and v were previously in different sets.
+    pub fn merge(&mut self, u: usize, v: usize) -> bool {
+        let (mut pu, mut pv) = (self.leader(u), self.leader(v));
+        if pu == pv {
+            return false;
+        }
+        if self.size[pu] > self.size[pv] {
+            std::mem::swap(&mut pu, &mut pv);
+        }
+        self.parent[pu] = pv;
+        self.size[pv] += self.size[pu];
+        self.components -= 1;
+        self.history.push((pu, pv));
+        true
+    }
+
+    #[must_use]
+    pub fn same(&self, u: usize, v: usize) -> bool {
+        self.leader(u) == self.leader(v)
+    }
+
+    /// Returns the size of the set containing u.
+    #[must_use]
+    pub fn size(&self, u: usize) -> usize {
+        self.size[self.leader(u)]
+    }
+
+    /// Returns the number of disjoint sets.
+    #[must_use]
+    pub fn count_components(&self) -> usize {
+        self.components
+    }
+
+    /// Returns a snapshot of the current state, which can be restored with
+    /// `rollback`.
+    #[must_use]
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Undoes all the merges made after `snapshot` was taken.
+    pub fn rollback(&mut self, snapshot: usize) {
+        while self.history.len() > snapshot {
+            let Some((pu, pv)) = self.history.pop() else {
+                break;
+            };
+            self.parent[pu] = pu;
+            self.size[pv] -= self.size[pu];
+            self.components += 1;
+        }
+    }
+}
+
+/// Offline dynamic connectivity in `O((e log t + q) log n)`.
+///
+/// Time points are `0..times`. Each of `edges` is `((u, v), alive)`, where the
+/// undirected edge `u - v` exists at time points `alive`. Each of `queries` is
+/// `(time, (u, v))`. Returns whether `u` and `v` are connected at `time` for each
+/// query.
+///
+/// Every edge is put into `O(log t)` nodes of a segment tree over time, which is
+/// then traversed with a [`RollbackDisjointSets`].
+#[must_use]
+pub fn dynamic_connectivity(
+    n: usize,
+    times: usize,
+    edges: &[((usize, usize), Range<usize>)],
+    queries: &[(usize, (usize, usize))],
+) -> Vec<bool> {
+    let mut tree = vec![Vec::new(); 4 * times];
+    let root = Node::root(times);
+    for (edge, alive) in edges {
+        add_edge_recursive(&mut tree, &root, alive.start..min(alive.end, times), *edge);
+    }
+    let mut queries_at = vec![Vec::new(); times];
+    for (query_id, &(time, _)) in queries.iter().enumerate() {
+        queries_at[time].push(query_id);
+    }
+    let mut answers = vec![false; queries.len()];
+    let mut dsets = RollbackDisjointSets::new(n);
+    let mut traversal = Traversal {
+        tree: &tree,
+        queries,
+        queries_at: &queries_at,
+        answers: &mut answers,
+    };
+    if times > 0 {
+        traversal.solve_recursive(&root, &mut dsets);
+    }
+    answers
+}
+
+fn add_edge_recursive(
+    tree: &mut [Vec<(usize, usize)>],
+    node: &Node,
+    alive: Range<usize>,
+    edge: (usize, usize),
+) {
+    if alive.is_empty() {
+        return;
+    }
+    if node.range == alive {
+        tree[node.id].push(edge);
+        return;
+    }
+    let (left_node, right_node) = node.children();
+    let left_alive = alive.start..min(alive.end, left_node.range.end);
+    let right_alive = max(alive.start, right_node.range.start)..alive.end;
+    add_edge_recursive(tree, &left_node, left_alive, edge);
+    add_edge_recursive(tree, &right_node, right_alive, edge);
+}
+
+struct Traversal<'a> {
+    tree: &'a [Vec<(usize, usize)>],
+    queries: &'a [(usize, (usize, usize))],
+    queries_at: &'a [Vec<usize>],
+    answers: &'a mut [bool],
+}
+
+impl Traversal<'_> {
+    fn solve_recursive(&mut self, node: &Node, dsets: &mut RollbackDisjointSets) {
+        let snapshot = dsets.snapshot();
+        for &(u, v) in &self.tree[node.id] {
+            dsets.merge(u, v);
+        }
+        if node.range.len() == 1 {
+            for &query_id in &self.queries_at[node.range.start] {
+                let (_, (u, v)) = self.queries[query_id];
+                self.answers[query_id] = dsets.same(u, v);
+            }
+        } else {
+            let (left_node, right_node) = node.children();
+            self.solve_recursive(&left_node, dsets);
+            self.solve_recursive(&right_node, dsets);
+        }
+        dsets.rollback(snapshot);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use proptest::prelude::*;
+
     #[test]
     #[allow(clippy::bool_assert_comparison)]
     fn test_disjoint_sets() {
@@ -103,4 +423,115 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_rollback() {
+        let mut dsets = RollbackDisjointSets::new(6);
+        dsets.merge(0, 1);
+        let snapshot = dsets.snapshot();
+        assert!(dsets.merge(2, 3));
+        assert!(dsets.merge(1, 3));
+        assert!(!dsets.merge(0, 2));
+        assert!(dsets.same(0, 3));
+        assert_eq!(dsets.size(2), 4);
+        assert_eq!(dsets.count_components(), 3);
+        dsets.rollback(snapshot);
+        assert!(dsets.same(0, 1));
+        assert!(!dsets.same(0, 3));
+        assert!(!dsets.same(2, 3));
+        assert_eq!(dsets.size(2), 1);
+        assert_eq!(dsets.size(1), 2);
+        assert_eq!(dsets.count_components(), 5);
+        dsets.rollback(0);
+        assert_eq!(dsets.count_components(), 6);
+    }
+
+    proptest! {
+        #[test]
+        fn dynamic_connectivity_matches_naive(
+            n in 1..8_usize,
+            times in 0..12_usize,
+            edges in prop::collection::vec(((0..8_usize, 0..8_usize), 0..12_usize, 0..12_usize), 0..15),
+            queries in prop::collection::vec((0..12_usize, (0..8_usize, 0..8_usize)), 0..30),
+        ) {
+            let edges: Vec<_> = edges
+                .into_iter()
+                .map(|((u, v), i, j)| ((u % n, v % n), min(i, j)..max(i, j)))
+                .collect();
+            let queries: Vec<_> = queries
+                .into_iter()
+                .filter(|&(time, _)| time < times)
+                .map(|(time, (u, v))| (time, (u % n, v % n)))
+                .collect();
+            let answers = dynamic_connectivity(n, times, &edges, &queries);
+            for (&(time, (u, v)), answer) in queries.iter().zip(answers) {
+                let mut dsets = DisjointSets::new(n);
+                for &((eu, ev), ref alive) in &edges {
+                    if alive.contains(&time) {
+                        dsets.merge(eu, ev);
+                    }
+                }
+                prop_assert_eq!(answer, dsets.same(u, v));
+            }
+        }
+    }
+
+    #[test]
+    fn test_potentials() {
+        use crate::ds::monoid::Sum;
+
+        let mut dsets = PotentialDisjointSets::<Sum<i64>>::new(6);
+        // x = [0, 5, 2, 7, ?, ?] up to a shift
+        assert_eq!(dsets.merge(0, 1, &5), Ok(true));
+        assert_eq!(dsets.merge(2, 1, &3), Ok(true));
+        assert_eq!(dsets.merge(3, 2, &-5), Ok(true));
+        assert_eq!(dsets.merge(4, 5, &1), Ok(true));
+        assert_eq!(dsets.diff(0, 3), Some(7));
+        assert_eq!(dsets.diff(3, 0), Some(-7));
+        assert_eq!(dsets.diff(2, 2), Some(0));
+        assert_eq!(dsets.diff(0, 4), None);
+        assert_eq!(dsets.merge(3, 0, &-7), Ok(false));
+        assert_eq!(dsets.merge(3, 0, &7), Err(Contradiction));
+        assert_eq!(dsets.merge(5, 3, &10), Ok(true));
+        assert_eq!(dsets.diff(0, 4), Some(-4));
+        assert!(dsets.same(1, 4));
+    }
+
+    #[test]
+    fn test_parity() {
+        // Even cycle 0 - 1 - 2 - 3 - 0 is bipartite
+        let mut dsets = ParityDisjointSets::new(5);
+        for (u, v) in [(0, 1), (1, 2), (2, 3), (3, 0)] {
+            assert!(dsets.merge(u, v, &true).is_ok());
+        }
+        assert_eq!(dsets.diff(0, 2), Some(false));
+        assert_eq!(dsets.diff(1, 0), Some(true));
+        // Odd cycle 0 - 1 - 2 - 0 is not
+        assert_eq!(dsets.merge(2, 0, &true), Err(Contradiction));
+        assert_eq!(dsets.merge(2, 4, &true), Ok(true));
+        assert_eq!(dsets.merge(4, 1, &false), Ok(false));
+        assert_eq!(dsets.merge(4, 1, &true), Err(Contradiction));
+    }
+
+    #[test]
+    fn test_sizes_and_groups() {
+        let mut dsets = DisjointSets::new(8);
+        assert_eq!(dsets.count_components(), 8);
+        assert!(dsets.merge(0, 5));
+        assert!(dsets.merge(7, 5));
+        assert!(dsets.merge(3, 2));
+        assert!(!dsets.merge(0, 7));
+        assert_eq!(dsets.count_components(), 5);
+        assert_eq!(dsets.size(5), 3);
+        assert_eq!(dsets.size(0), 3);
+        assert_eq!(dsets.size(2), 2);
+        assert_eq!(dsets.size(4), 1);
+        assert_eq!(
+            dsets.groups(),
+            vec![vec![0, 5, 7], vec![1], vec![2, 3], vec![4], vec![6]]
+        );
+        assert!(dsets.merge(1, 7));
+        assert_eq!(dsets.size(0), 4);
+        assert_eq!(dsets.count_components(), 4);
+    }
 }
@@ -0,0 +1,362 @@
+use std::ops::{Add, Mul, Range, Sub};
+
+use super::from_usize;
+use crate::vec::Vec2;
+
+/// Fenwick Tree (Binary Indexed Tree): point add, prefix sums.
+/// `T::default()` is assumed to be zero.
+///
+/// `tree[i]` stores the sum of `arr[(i & (i + 1))..=i]`.
+///
+/// # Panics
+///
+/// May or may not panic if `idx`, `range` are out of bounds.
+#[derive(Debug, Clone)]
+pub struct FenwickTree<T> {
+    tree: Vec<T>,
+}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> FenwickTree<T> {
+    /// Returns a Fenwick tree for `n` array elements, assumed to be all zeroes.
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        Self {
+            tree: vec![T::default(); n],
+        }
+    }
+
+    /// Builds the tree in `O(n)`.
+    #[must_use]
+    pub fn from_slice(arr: &[T]) -> Self {
+        let mut tree = arr.to_vec();
+        for i in 0..tree.len() {
+            let parent = i | (i + 1);
+            if parent < tree.len() {
+                tree[parent] = tree[parent] + tree[i];
+            }
+        }
+        Self { tree }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Adds `delta` to `arr[idx]`.
+    pub fn add(&mut self, mut idx: usize, delta: T) {
+        while idx < self.tree.len() {
+            self.tree[idx] = self.tree[idx] + delta;
+            idx |= idx + 1;
+        }
+    }
+
+    /// Returns the sum of `arr[..end]`.
+    #[must_use]
+    pub fn prefix_sum(&self, mut end: usize) -> T {
+        let mut sum = T::default();
+        while end > 0 {
+            sum = sum + self.tree[end - 1];
+            end &= end - 1;
+        }
+        sum
+    }
+
+    /// Returns the sum of `arr[range]`.
+    #[must_use]
+    pub fn range_sum(&self, range: Range<usize>) -> T {
+        if range.is_empty() {
+            return T::default();
+        }
+        self.prefix_sum(range.end) - self.prefix_sum(range.start)
+    }
+}
+
+impl<T: Copy + Default + Ord + Add<Output = T> + Sub<Output = T>> FenwickTree<T> {
+    /// Returns the smallest `idx`, such that `prefix_sum(idx + 1) >= k`, or `len()`
+    /// if there is none. All elements must be non-negative.
+    ///
+    /// For a frequency array it is the `k`-th (1-indexed) smallest element.
+    #[must_use]
+    pub fn lower_bound(&self, mut k: T) -> usize {
+        let n = self.tree.len();
+        // Number of elements, which are known to have prefix sum less than `k`
+        let mut pos = 0;
+        let mut step = if n == 0 { 0 } else { 1 << n.ilog2() };
+        while step > 0 {
+            if pos + step <= n && self.tree[pos + step - 1] < k {
+                pos += step;
+                k = k - self.tree[pos - 1];
+            }
+            step /= 2;
+        }
+        pos
+    }
+}
+
+/// Fenwick Tree on the difference array: range add, point query.
+/// `T::default()` is assumed to be zero.
+#[derive(Debug, Clone)]
+pub struct RangeAddPointQuery<T> {
+    diff: FenwickTree<T>,
+}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> RangeAddPointQuery<T> {
+    /// Returns a Fenwick tree for `n` array elements, assumed to be all zeroes.
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        Self {
+            diff: FenwickTree::new(n),
+        }
+    }
+
+    /// Adds `delta` to every element in `range`.
+    pub fn add(&mut self, range: Range<usize>, delta: T) {
+        if range.is_empty() {
+            return;
+        }
+        self.diff.add(range.start, delta);
+        if range.end < self.diff.len() {
+            self.diff.add(range.end, T::default() - delta);
+        }
+    }
+
+    /// Returns `arr[idx]`.
+    #[must_use]
+    pub fn get(&self, idx: usize) -> T {
+        self.diff.prefix_sum(idx + 1)
+    }
+}
+
+/// Two Fenwick Trees: range add, range sum.
+/// `T::default()` is assumed to be zero.
+///
+/// With `d` being the difference array,
+/// `sum(arr[..i]) = i * sum(d[..i]) - sum(j * d[j] for j < i)`.
+#[derive(Debug, Clone)]
+pub struct RangeAddRangeSum<T> {
+    diff: FenwickTree<T>,
+    weighted_diff: FenwickTree<T>,
+}
+
+impl<T> RangeAddRangeSum<T>
+where
+    T: Copy
+        + Default
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + TryFrom<usize>,
+{
+    /// Returns a Fenwick tree for `n` array elements, assumed to be all zeroes.
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        Self {
+            diff: FenwickTree::new(n),
+            weighted_diff: FenwickTree::new(n),
+        }
+    }
+
+    /// Adds `delta` to every element in `range`.
+    pub fn add(&mut self, range: Range<usize>, delta: T) {
+        if range.is_empty() {
+            return;
+        }
+        self.add_diff(range.start, delta);
+        if range.end < self.diff.len() {
+            self.add_diff(range.end, T::default() - delta);
+        }
+    }
+
+    /// Returns the sum of `arr[..end]`.
+    #[must_use]
+    pub fn prefix_sum(&self, end: usize) -> T {
+        self.diff.prefix_sum(end) * from_usize(end) - self.weighted_diff.prefix_sum(end)
+    }
+
+    /// Returns the sum of `arr[range]`.
+    #[must_use]
+    pub fn range_sum(&self, range: Range<usize>) -> T {
+        if range.is_empty() {
+            return T::default();
+        }
+        self.prefix_sum(range.end) - self.prefix_sum(range.start)
+    }
+
+    fn add_diff(&mut self, idx: usize, delta: T) {
+        self.diff.add(idx, delta);
+        self.weighted_diff.add(idx, delta * from_usize(idx));
+    }
+}
+
+/// 2D Fenwick Tree: point add, rectangle sums.
+/// `T::default()` is assumed to be zero.
+///
+/// Rectangles are half-open, see [`super::prefix_sum_2d`].
+#[derive(Debug, Clone)]
+pub struct FenwickTree2d<T> {
+    tree: Vec2<T>,
+}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> FenwickTree2d<T> {
+    /// Returns a Fenwick tree for a grid of the given `dims`, assumed to be all
+    /// zeroes.
+    #[must_use]
+    pub fn new(dims: (usize, usize)) -> Self {
+        Self {
+            tree: Vec2::new(dims),
+        }
+    }
+
+    /// Adds `delta` to `grid[(r, c)]`.
+    pub fn add(&mut self, (mut r, c): (usize, usize), delta: T) {
+        let (n, m) = self.tree.dims();
+        while r < n {
+            let mut c = c;
+            while c < m {
+                self.tree[(r, c)] = self.tree[(r, c)] + delta;
+                c |= c + 1;
+            }
+            r |= r + 1;
+        }
+    }
+
+    /// Returns the sum of rows `..r` and columns `..c`.
+    #[must_use]
+    pub fn prefix_sum(&self, (mut r, c): (usize, usize)) -> T {
+        let mut sum = T::default();
+        while r > 0 {
+            let mut c = c;
+            while c > 0 {
+                sum = sum + self.tree[(r - 1, c - 1)];
+                c &= c - 1;
+            }
+            r &= r - 1;
+        }
+        sum
+    }
+
+    /// Returns the sum of rows `r1..r2` and columns `c1..c2`.
+    #[must_use]
+    pub fn rect_sum(&self, (r1, c1): (usize, usize), (r2, c2): (usize, usize)) -> T {
+        if r1 >= r2 || c1 >= c2 {
+            return T::default();
+        }
+        self.prefix_sum((r2, c2)) - self.prefix_sum((r1, c2)) - self.prefix_sum((r2, c1))
+            + self.prefix_sum((r1, c1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    type Int = i64;
+
+    #[test]
+    fn test_basic() {
+        let arr: [Int; 6] = [0, 2, -5, 17, -100, 44];
+        let mut ft = FenwickTree::from_slice(&arr);
+        assert_eq!(ft.prefix_sum(0), 0);
+        assert_eq!(ft.prefix_sum(6), -42);
+        assert_eq!(ft.range_sum(1..4), 14);
+        ft.add(2, 5);
+        assert_eq!(ft.range_sum(1..4), 19);
+        assert_eq!(ft.range_sum(3..3), 0);
+    }
+
+    #[test]
+    fn test_lower_bound() {
+        // Frequencies of values 0..6: the multiset is {1, 1, 3, 4, 4, 4}
+        let ft = FenwickTree::<Int>::from_slice(&[0, 2, 0, 1, 3, 0]);
+        assert_eq!(ft.lower_bound(1), 1);
+        assert_eq!(ft.lower_bound(2), 1);
+        assert_eq!(ft.lower_bound(3), 3);
+        assert_eq!(ft.lower_bound(4), 4);
+        assert_eq!(ft.lower_bound(6), 4);
+        assert_eq!(ft.lower_bound(7), 6);
+        assert_eq!(ft.lower_bound(0), 0);
+        assert_eq!(FenwickTree::<Int>::new(0).lower_bound(1), 0);
+    }
+
+    #[test]
+    fn test_2d() {
+        let (n, m) = (4, 5);
+        let mut grid: Vec2<Int> = Vec2::new((n, m));
+        let mut ft = FenwickTree2d::new((n, m));
+        for (i, delta) in [3, -1, 4, 1, -5, 9, 2, -6, 5, 3].into_iter().enumerate() {
+            let idx = (i * 7 % n, i * 3 % m);
+            grid[idx] += delta;
+            ft.add(idx, delta);
+        }
+        for r1 in 0..=n {
+            for r2 in r1..=n {
+                for c1 in 0..=m {
+                    for c2 in c1..=m {
+                        let expected: Int = (r1..r2)
+                            .flat_map(|r| (c1..c2).map(move |c| (r, c)))
+                            .map(|idx| grid[idx])
+                            .sum();
+                        assert_eq!(ft.rect_sum((r1, c1), (r2, c2)), expected);
+                    }
+                }
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn point_add_matches_naive(
+            mut arr in prop::collection::vec(0..=100 as Int, 1..50),
+            queries in prop::collection::vec((0..50_usize, 0..=100 as Int), 0..30),
+        ) {
+            let mut ft = FenwickTree::from_slice(&arr);
+            for (idx, delta) in queries {
+                let idx = idx % arr.len();
+                arr[idx] += delta;
+                ft.add(idx, delta);
+            }
+            for end in 0..=arr.len() {
+                let prefix: Int = arr[..end].iter().sum();
+                prop_assert_eq!(ft.prefix_sum(end), prefix);
+            }
+            let total: Int = arr.iter().sum();
+            for k in 1..=total {
+                let expected = (0..arr.len())
+                    .find(|&idx| arr[..=idx].iter().sum::<Int>() >= k)
+                    .unwrap();
+                prop_assert_eq!(ft.lower_bound(k), expected);
+            }
+        }
+
+        #[test]
+        fn range_add_matches_naive(
+            n in 1..50_usize,
+            queries in prop::collection::vec((0..=50_usize, 0..=50_usize, -100..=100 as Int), 0..30),
+        ) {
+            let mut arr = vec![0; n];
+            let mut point = RangeAddPointQuery::new(n);
+            let mut range = RangeAddRangeSum::new(n);
+            for (i, j, delta) in queries {
+                let (i, j) = (i.min(n), j.min(n));
+                let rng = i.min(j)..i.max(j);
+                arr[rng.clone()].iter_mut().for_each(|x| *x += delta);
+                point.add(rng.clone(), delta);
+                range.add(rng, delta);
+            }
+            for i in 0..n {
+                prop_assert_eq!(point.get(i), arr[i]);
+                for j in i..=n {
+                    prop_assert_eq!(range.range_sum(i..j), arr[i..j].iter().sum::<Int>());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use std::{cmp::Ordering, ops::Range};
+
+use super::{monoid::Monoid, treap::TreapNode};
+use crate::rng::Rng;
+
+/// Implicit (sequence) treap: a Cartesian tree keyed by position, with random
+/// weights. Supports insertion/removal at any index, splitting, concatenation and
+/// range reversal in `O(log n)`, and range aggregates over the monoid `M`.
+///
+/// Shares split and merge with [`Treap`](super::treap::Treap), only the split
+/// point is located by the subtree sizes instead of the keys.
+///
+/// `M` does not have to be commutative: each node keeps aggregates of its
+/// subtree in both directions, which are swapped on reversal.
+///
+/// # Panics
+///
+/// May or may not panic if `idx`, `range` are out of bounds.
+pub struct ImplicitTreap<M: Monoid> {
+    root: NodePtr<M>,
+    rng: Rng,
+}
+
+type NodePtr<M> = Option<Box<Node<M>>>;
+
+impl<M: Monoid> ImplicitTreap<M> {
+    /// Returns an empty treap with weights seeded from entropy.
+    #[must_use]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self::with_rng(Rng::from_entropy())
+    }
+
+    /// Returns an empty treap, whose shape is determined by `seed`.
+    #[must_use]
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(Rng::new(seed))
+    }
+
+    fn with_rng(rng: Rng) -> Self {
+        Self { root: None, rng }
+    }
+
+    /// Returns a treap of the elements of `arr`, with weights seeded from
+    /// entropy.
+    #[must_use]
+    pub fn from_slice(arr: &[M::Value]) -> Self {
+        Self::from_slice_with_rng(arr, Rng::from_entropy())
+    }
+
+    /// Like [`ImplicitTreap::from_slice`], but the shape is determined by `seed`.
+    #[must_use]
+    pub fn from_slice_with_seed(arr: &[M::Value], seed: u64) -> Self {
+        Self::from_slice_with_rng(arr, Rng::new(seed))
+    }
+
+    fn from_slice_with_rng(arr: &[M::Value], rng: Rng) -> Self {
+        let mut treap = Self::with_rng(rng);
+        for value in arr {
+            treap.push(value.clone());
+        }
+        treap
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        Node::size(self.root.as_deref())
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Appends `value` to the end of the sequence.
+    pub fn push(&mut self, value: M::Value) {
+        self.insert(self.len(), value);
+    }
+
+    /// Inserts `value` at position `idx`, shifting all elements after it.
+    pub fn insert(&mut self, idx: usize, value: M::Value) {
+        let weight = self.rng.next_u32();
+        let (left_tree, right_tree) = Node::split_at(self.root.take(), idx);
+        let new_node = Some(Box::new(Node::new(value, weight)));
+        self.root = Node::merge(Node::merge(left_tree, new_node), right_tree);
+    }
+
+    /// Removes and returns the element at position `idx`, or `None` if
+    /// `idx >= len()`.
+    pub fn remove(&mut self, idx: usize) -> Option<M::Value> {
+        let (left_tree, right_tree) = Node::split_at(self.root.take(), idx);
+        let (idx_node, right_tree) = Node::split_at(right_tree, 1);
+        self.root = Node::merge(left_tree, right_tree);
+        idx_node.map(|n| n.value)
+    }
+
+    /// Splits the sequence in two at `idx`: `self` keeps `[0, idx)`,
+    /// and `[idx, len)` is returned.
+    #[must_use]
+    pub fn split_off(&mut self, idx: usize) -> Self {
+        let (left_tree, right_tree) = Node::split_at(self.root.take(), idx);
+        self.root = left_tree;
+        Self {
+            root: right_tree,
+            rng: Rng::new(self.rng.next_u64()),
+        }
+    }
+
+    /// Moves all elements of `other` to the end of `self`, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        self.root = Node::merge(self.root.take(), other.root.take());
+    }
+
+    /// Reverses the elements in `range`.
+    pub fn reverse(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let (left_tree, mid_tree, right_tree) =
+            Node::split_range(self.root.take(), range);
+        let mid_tree = mid_tree.map(|mut n| {
+            n.toggle_reversed();
+            n
+        });
+        self.root = Node::merge(Node::merge(left_tree, mid_tree), right_tree);
+    }
+
+    /// Returns the aggregate of the elements in `range`.
+    pub fn compute(&mut self, range: Range<usize>) -> M::Value {
+        if range.is_empty() {
+            return M::identity();
+        }
+        let (left_tree, mid_tree, right_tree) =
+            Node::split_range(self.root.take(), range);
+        let value = Node::agg(mid_tree.as_deref());
+        self.root = Node::merge(Node::merge(left_tree, mid_tree), right_tree);
+        value
+    }
+
+    /// Returns the elements in order.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<M::Value> {
+        let mut res = Vec::with_capacity(self.len());
+        Node::collect(self.root.as_deref(), false, &mut res);
+        res
+    }
+}
+
+struct Node<M: Monoid> {
+    value: M::Value,
+    weight: u32,
+    left: NodePtr<M>,
+    right: NodePtr<M>,
+    size: usize,
+    /// Aggregate of the subtree in order.
+    agg: M::Value,
+    /// Aggregate of the subtree in reverse order.
+    rev_agg: M::Value,
+    /// Whether the children have to be swapped and reversed. `agg` and `rev_agg`
+    /// of this node are already up to date.
+    reversed: bool,
+}
+
+impl<M: Monoid> Node<M> {
+    fn new(value: M::Value, weight: u32) -> Self {
+        Self {
+            agg: value.clone(),
+            rev_agg: value.clone(),
+            value,
+            weight,
+            left: None,
+            right: None,
+            size: 1,
+            reversed: false,
+        }
+    }
+
+    /// Splits this treap into 2 treaps: `([0, idx), [idx, len))`.
+    fn split_at(node: NodePtr<M>, mut idx: usize) -> (NodePtr<M>, NodePtr<M>) {
+        let (left_tree, _, right_tree) = Node::split(node, &mut |node: &Self| {
+            let left_size = Node::size(node.left.as_deref());
+            if idx <= left_size {
+                Ordering::Less
+            } else {
+                idx -= left_size + 1;
+                Ordering::Greater
+            }
+        });
+        (left_tree, right_tree)
+    }
+
+    /// Splits this treap into 3 treaps: `([0, start), range, [end, len))`.
+    fn split_range(
+        node: NodePtr<M>,
+        range: Range<usize>,
+    ) -> (NodePtr<M>, NodePtr<M>, NodePtr<M>) {
+        let (left_tree, right_tree) = Node::split_at(node, range.start);
+        let (mid_tree, right_tree) = Node::split_at(right_tree, range.len());
+        (left_tree, mid_tree, right_tree)
+    }
+
+    fn size(node: Option<&Node<M>>) -> usize {
+        node.map_or(0, |n| n.size)
+    }
+
+    fn agg(node: Option<&Node<M>>) -> M::Value {
+        node.map_or_else(M::identity, |n| n.agg.clone())
+    }
+
+    fn rev_agg(node: Option<&Node<M>>) -> M::Value {
+        node.map_or_else(M::identity, |n| n.rev_agg.clone())
+    }
+
+    fn toggle_reversed(&mut self) {
+        self.reversed = !self.reversed;
+        std::mem::swap(&mut self.agg, &mut self.rev_agg);
+    }
+
+    fn collect(node: Option<&Node<M>>, reversed: bool, res: &mut Vec<M::Value>) {
+        let Some(node) = node else { return };
+        let reversed = reversed ^ node.reversed;
+        let (first, second) = if reversed {
+            (node.right.as_deref(), node.left.as_deref())
+        } else {
+            (node.left.as_deref(), node.right.as_deref())
+        };
+        Node::collect(first, reversed, res);
+        res.push(node.value.clone());
+        Node::collect(second, reversed, res);
+    }
+}
+
+impl<M: Monoid> TreapNode for Node<M> {
+    type Weight = u32;
+
+    fn weight(&self) -> &u32 {
+        &self.weight
+    }
+
+    fn left(&mut self) -> &mut NodePtr<M> {
+        &mut self.left
+    }
+
+    fn right(&mut self) -> &mut NodePtr<M> {
+        &mut self.right
+    }
+
+    /// Pushes the reversal down to the children.
+    fn push(&mut self) {
+        if !self.reversed {
+            return;
+        }
+        self.reversed = false;
+        std::mem::swap(&mut self.left, &mut self.right);
+        for child in [self.left.as_deref_mut(), self.right.as_deref_mut()]
+            .into_iter()
+            .flatten()
+        {
+            child.toggle_reversed();
+        }
+    }
+
+    fn update(&mut self) {
+        let (left, right) = (self.left.as_deref(), self.right.as_deref());
+        self.size = 1 + Node::size(left) + Node::size(right);
+        self.agg = M::op(&M::op(&Node::agg(left), &self.value), &Node::agg(right));
+        self.rev_agg = M::op(
+            &M::op(&Node::rev_agg(right), &self.value),
+            &Node::rev_agg(left),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ds::monoid::Sum;
+
+    use proptest::prelude::*;
+
+    type Int = i64;
+
+    /// Non-commutative monoid: concatenation of strings.
+    struct Concat;
+
+    impl Monoid for Concat {
+        type Value = String;
+
+        fn identity() -> String {
+            String::new()
+        }
+
+        fn op(a: &String, b: &String) -> String {
+            format!("{a}{b}")
+        }
+    }
+
+    #[test]
+    fn test_basic() {
+        let letters: Vec<String> = "abcdefg".chars().map(String::from).collect();
+        let mut treap = ImplicitTreap::<Concat>::from_slice(&letters);
+        assert_eq!(treap.len(), 7);
+        assert_eq!(treap.compute(0..7), "abcdefg");
+        treap.reverse(1..5);
+        assert_eq!(treap.compute(0..7), "aedcbfg");
+        assert_eq!(treap.compute(2..6), "dcbf");
+        treap.reverse(0..3);
+        assert_eq!(treap.compute(0..7), "deacbfg");
+        treap.insert(3, "X".to_string());
+        assert_eq!(treap.remove(0), Some("d".to_string()));
+        assert_eq!(treap.remove(7), None);
+        assert_eq!(treap.compute(0..7), "eaXcbfg");
+
+        let mut tail = treap.split_off(4);
+        assert_eq!(treap.to_vec().concat(), "eaXc");
+        assert_eq!(tail.to_vec().concat(), "bfg");
+        tail.reverse(0..3);
+        tail.append(&mut treap);
+        assert!(treap.is_empty());
+        assert_eq!(tail.compute(0..7), "gfbeaXc");
+        assert_eq!(tail.compute(3..3), "");
+    }
+
+    /// Weights of the nodes in preorder, which determine the shape of a treap.
+    fn weights<M: Monoid>(node: Option<&Node<M>>, res: &mut Vec<u32>) {
+        let Some(node) = node else { return };
+        res.push(node.weight);
+        weights(node.left.as_deref(), res);
+        weights(node.right.as_deref(), res);
+    }
+
+    #[test]
+    fn test_with_seed() {
+        let arr: Vec<Int> = (0..100).collect();
+        let shape = |treap: &ImplicitTreap<Sum<Int>>| {
+            let mut res = Vec::new();
+            weights(treap.root.as_deref(), &mut res);
+            res
+        };
+        let a = ImplicitTreap::<Sum<Int>>::from_slice_with_seed(&arr, 5);
+        let b = ImplicitTreap::<Sum<Int>>::from_slice_with_seed(&arr, 5);
+        let c = ImplicitTreap::<Sum<Int>>::from_slice_with_seed(&arr, 6);
+        assert_eq!(shape(&a), shape(&b));
+        assert_ne!(shape(&a), shape(&c));
+        assert_eq!(a.to_vec(), arr);
+    }
+
+    #[derive(Debug, Clone)]
+    enum Query {
+        Insert(usize, Int),
+        Remove(usize),
+        Reverse(usize, usize),
+        Compute(usize, usize),
+    }
+
+    fn query_strategy() -> impl Strategy<Value = Query> {
+        prop_oneof![
+            (0..50_usize, -100..=100 as Int)
+                .prop_map(|(idx, val)| Query::Insert(idx, val)),
+            (0..50_usize).prop_map(Query::Remove),
+            (0..50_usize, 0..50_usize).prop_map(|(i, j)| Query::Reverse(i, j)),
+            (0..50_usize, 0..50_usize).prop_map(|(i, j)| Query::Compute(i, j)),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn matches_vec(
+            arr in prop::collection::vec(-100..=100 as Int, 0..30),
+            queries in prop::collection::vec(query_strategy(), 0..60),
+        ) {
+            // `Concat` is not commutative, so every reversal has to flip the
+            // aggregates of the subtrees correctly
+            let mut arr: Vec<String> = arr.iter().map(|val| format!("{val},")).collect();
+            let mut treap = ImplicitTreap::<Concat>::from_slice(&arr);
+            for query in queries {
+                let n = arr.len();
+                match query {
+                    Query::Insert(idx, val) => {
+                        let idx = idx % (n + 1);
+                        arr.insert(idx, format!("{val},"));
+                        treap.insert(idx, format!("{val},"));
+                    }
+                    Query::Remove(idx) => {
+                        let expected = (idx < n).then(|| arr.remove(idx));
+                        prop_assert_eq!(treap.remove(idx), expected);
+                    }
+                    Query::Reverse(i, j) => {
+                        let (i, j) = (i % (n + 1), j % (n + 1));
+                        let range = i.min(j)..i.max(j);
+                        arr[range.clone()].reverse();
+                        treap.reverse(range);
+                    }
+                    Query::Compute(i, j) => {
+                        let (i, j) = (i % (n + 1), j % (n + 1));
+                        let range = i.min(j)..i.max(j);
+                        prop_assert_eq!(treap.compute(range.clone()), arr[range].concat());
+                    }
+                }
+                prop_assert_eq!(treap.len(), arr.len());
+            }
+            prop_assert_eq!(treap.to_vec(), arr);
+        }
+    }
+}
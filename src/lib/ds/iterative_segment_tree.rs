@@ -0,0 +1,156 @@
+use std::ops::Range;
+
+use super::monoid::Monoid;
+
+/// Non-recursive bottom-up Segment Tree over the monoid `M` with `2 * n` memory.
+/// Works for any `n`, not only powers of two, and preserves the order of
+/// operands, so `M` does not have to be commutative.
+///
+/// Leaves are stored at `tree[n..2 * n]`, parent of node `i` is `i / 2`.
+///
+/// # Panics
+///
+/// May or may not panic if `n`, `range`, `idx` are out of bounds.
+pub struct IterativeSegmentTree<M: Monoid> {
+    tree: Vec<M::Value>,
+    len: usize,
+}
+
+impl<M: Monoid> IterativeSegmentTree<M> {
+    /// Returns a segment tree for `n` array elements, assumed to be all
+    /// `M::identity()`.
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        Self {
+            tree: vec![M::identity(); 2 * n],
+            len: n,
+        }
+    }
+
+    #[must_use]
+    pub fn from_slice(arr: &[M::Value]) -> Self {
+        let len = arr.len();
+        let mut tree = Vec::with_capacity(2 * len);
+        tree.resize(len, M::identity());
+        tree.extend_from_slice(arr);
+        for i in (1..len).rev() {
+            tree[i] = M::op(&tree[2 * i], &tree[2 * i + 1]);
+        }
+        Self { tree, len }
+    }
+
+    #[must_use]
+    pub fn compute(&self, range: Range<usize>) -> M::Value {
+        let (mut l, mut r) = (range.start + self.len, range.end + self.len);
+        let (mut left_acc, mut right_acc) = (M::identity(), M::identity());
+        while l < r {
+            if l % 2 == 1 {
+                left_acc = M::op(&left_acc, &self.tree[l]);
+                l += 1;
+            }
+            if r % 2 == 1 {
+                r -= 1;
+                right_acc = M::op(&self.tree[r], &right_acc);
+            }
+            l /= 2;
+            r /= 2;
+        }
+        M::op(&left_acc, &right_acc)
+    }
+
+    pub fn update(&mut self, idx: usize, value: M::Value) {
+        let mut i = idx + self.len;
+        self.tree[i] = value;
+        while i > 1 {
+            i /= 2;
+            self.tree[i] = M::op(&self.tree[2 * i], &self.tree[2 * i + 1]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ds::monoid::{Min, Sum};
+
+    use proptest::prelude::*;
+
+    type Int = i64;
+
+    /// Non-commutative monoid: concatenation of strings.
+    struct Concat;
+
+    impl Monoid for Concat {
+        type Value = String;
+
+        fn identity() -> String {
+            String::new()
+        }
+
+        fn op(a: &String, b: &String) -> String {
+            format!("{a}{b}")
+        }
+    }
+
+    #[allow(clippy::reversed_empty_ranges)]
+    fn check_st<M: Monoid>(arr: &[M::Value], st: &IterativeSegmentTree<M>)
+    where
+        M::Value: PartialEq + std::fmt::Debug,
+    {
+        for i in 0..=arr.len() {
+            for j in i..=arr.len() {
+                let expected = arr[i..j]
+                    .iter()
+                    .fold(M::identity(), |acc, x| M::op(&acc, x));
+                assert_eq!(st.compute(i..j), expected, "i = {i}, j = {j}");
+            }
+        }
+        assert_eq!(st.compute(1..0), M::identity());
+    }
+
+    #[test]
+    fn test() {
+        let mut arr = [0, 2, -5, 17, -100, 44];
+        let mut st = IterativeSegmentTree::<Sum<Int>>::from_slice(&arr);
+        check_st(&arr, &st);
+        for (idx, value) in [(0, 15), (2, -123), (1, 38), (5, -11), (3, 8)] {
+            arr[idx] = value;
+            st.update(idx, value);
+            check_st(&arr, &st);
+        }
+
+        let st = IterativeSegmentTree::<Sum<Int>>::new(5);
+        check_st(&[0; 5], &st);
+    }
+
+    #[test]
+    fn test_non_commutative() {
+        for n in 0..12 {
+            let mut arr: Vec<String> =
+                (0..n).map(|i| char::from(b'a' + i).to_string()).collect();
+            let mut st = IterativeSegmentTree::<Concat>::from_slice(&arr);
+            check_st(&arr, &st);
+            if n > 0 {
+                arr[usize::from(n / 2)] = "XY".to_string();
+                st.update(usize::from(n / 2), "XY".to_string());
+                check_st(&arr, &st);
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn min_matches_naive(
+            mut arr in prop::collection::vec(-1000..=1000 as Int, 1..50),
+            updates in prop::collection::vec((0..50_usize, -1000..=1000 as Int), 0..20),
+        ) {
+            let mut st = IterativeSegmentTree::<Min<Int>>::from_slice(&arr);
+            for (idx, value) in updates {
+                let idx = idx % arr.len();
+                arr[idx] = value;
+                st.update(idx, value);
+            }
+            check_st(&arr, &st);
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use std::{
+    cmp::{max, min},
+    marker::PhantomData,
+    ops::{Add, Mul, Range},
+};
+
+use super::{
+    from_usize,
+    monoid::{Bounded, Min, Monoid, Sum},
+    segment_tree::Node,
+};
+
+type Value<L> = <<L as LazyMap>::M as Monoid>::Value;
+
+/// Lazy tags, which act on the values of the monoid `M`.
+///
+/// `map` must distribute over `M::op`, i.e. applying a tag to the aggregate of
+/// a segment must be the same as aggregating the tagged elements.
+pub trait LazyMap {
+    type M: Monoid;
+    type Tag: Clone;
+
+    /// Tag which leaves any value unchanged.
+    fn identity_tag() -> Self::Tag;
+
+    /// Applies `tag` to `value`, which is the aggregate of `len` elements.
+    fn map(tag: &Self::Tag, value: &Value<Self>, len: usize) -> Value<Self>;
+
+    /// Returns the tag equivalent to applying `inner` first and `outer` second.
+    fn compose(outer: &Self::Tag, inner: &Self::Tag) -> Self::Tag;
+}
+
+/// Segment Tree with lazy propagation: applies tags of `L` to ranges and computes
+/// aggregates of `L::M` over ranges.
+///
+/// # Panics
+///
+/// May or may not panic if `n`, `range` are out of bounds.
+pub struct LazySegmentTree<L: LazyMap> {
+    tree: Vec<Value<L>>,
+    lazy: Vec<L::Tag>,
+    len: usize,
+}
+
+impl<L: LazyMap> LazySegmentTree<L> {
+    /// Returns a segment tree for `n` array elements, assumed to be all
+    /// `L::M::identity()`.
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        Self {
+            tree: vec![L::M::identity(); 4 * n],
+            lazy: vec![L::identity_tag(); 4 * n],
+            len: n,
+        }
+    }
+
+    #[must_use]
+    pub fn from_slice(arr: &[Value<L>]) -> Self {
+        let mut st = Self::new(arr.len());
+        st.build_recursive(arr, &Node::root(arr.len()));
+        st
+    }
+
+    pub fn compute(&mut self, range: Range<usize>) -> Value<L> {
+        self.compute_recursive(&Node::root(self.len), range)
+    }
+
+    /// Applies `tag` to every element in `range`.
+    pub fn apply(&mut self, range: Range<usize>, tag: &L::Tag) {
+        self.apply_recursive(&Node::root(self.len), range, tag);
+    }
+
+    fn build_recursive(&mut self, arr: &[Value<L>], node: &Node) {
+        match node.range.len() {
+            0 => (),
+            1 => {
+                self.tree[node.id] = arr[node.range.start].clone();
+            }
+            _ => {
+                let (left_node, right_node) = node.children();
+                self.build_recursive(arr, &left_node);
+                self.build_recursive(arr, &right_node);
+                self.pull(node, &left_node, &right_node);
+            }
+        }
+    }
+
+    fn compute_recursive(
+        &mut self,
+        node: &Node,
+        compute_range: Range<usize>,
+    ) -> Value<L> {
+        if compute_range.is_empty() {
+            return L::M::identity();
+        }
+        if node.range == compute_range {
+            return self.tree[node.id].clone();
+        }
+        let (left_node, right_node) = node.children();
+        self.push(node, &left_node, &right_node);
+        let left_compute_range =
+            compute_range.start..min(compute_range.end, left_node.range.end);
+        let right_compute_range =
+            max(compute_range.start, right_node.range.start)..compute_range.end;
+        let left_value = self.compute_recursive(&left_node, left_compute_range);
+        let right_value = self.compute_recursive(&right_node, right_compute_range);
+        L::M::op(&left_value, &right_value)
+    }
+
+    fn apply_recursive(&mut self, node: &Node, apply_range: Range<usize>, tag: &L::Tag) {
+        if apply_range.is_empty() {
+            return;
+        }
+        if node.range == apply_range {
+            self.apply_node(node, tag);
+            return;
+        }
+        let (left_node, right_node) = node.children();
+        self.push(node, &left_node, &right_node);
+        let left_apply_range =
+            apply_range.start..min(apply_range.end, left_node.range.end);
+        let right_apply_range =
+            max(apply_range.start, right_node.range.start)..apply_range.end;
+        self.apply_recursive(&left_node, left_apply_range, tag);
+        self.apply_recursive(&right_node, right_apply_range, tag);
+        self.pull(node, &left_node, &right_node);
+    }
+
+    fn apply_node(&mut self, node: &Node, tag: &L::Tag) {
+        self.tree[node.id] = L::map(tag, &self.tree[node.id], node.range.len());
+        if node.range.len() > 1 {
+            self.lazy[node.id] = L::compose(tag, &self.lazy[node.id]);
+        }
+    }
+
+    /// Pushes the lazy tag of `node` down to its children.
+    fn push(&mut self, node: &Node, left_node: &Node, right_node: &Node) {
+        let tag = std::mem::replace(&mut self.lazy[node.id], L::identity_tag());
+        self.apply_node(left_node, &tag);
+        self.apply_node(right_node, &tag);
+    }
+
+    fn pull(&mut self, node: &Node, left_node: &Node, right_node: &Node) {
+        self.tree[node.id] =
+            L::M::op(&self.tree[left_node.id], &self.tree[right_node.id]);
+    }
+}
+
+/// Range add, range sum. `T::default()` is assumed to be zero.
+pub struct AddSum<T>(PhantomData<T>);
+
+impl<T> LazyMap for AddSum<T>
+where
+    T: Copy + Default + Add<Output = T> + Mul<Output = T> + TryFrom<usize>,
+{
+    type M = Sum<T>;
+    type Tag = T;
+
+    fn identity_tag() -> T {
+        T::default()
+    }
+
+    fn map(tag: &T, value: &T, len: usize) -> T {
+        *value + *tag * from_usize(len)
+    }
+
+    fn compose(outer: &T, inner: &T) -> T {
+        *outer + *inner
+    }
+}
+
+/// Range assign, range sum. `T::default()` is assumed to be zero.
+pub struct AssignSum<T>(PhantomData<T>);
+
+impl<T> LazyMap for AssignSum<T>
+where
+    T: Copy + Default + Add<Output = T> + Mul<Output = T> + TryFrom<usize>,
+{
+    type M = Sum<T>;
+    type Tag = Option<T>;
+
+    fn identity_tag() -> Option<T> {
+        None
+    }
+
+    fn map(tag: &Option<T>, value: &T, len: usize) -> T {
+        tag.map_or(*value, |assigned| assigned * from_usize(len))
+    }
+
+    fn compose(outer: &Option<T>, inner: &Option<T>) -> Option<T> {
+        outer.or(*inner)
+    }
+}
+
+/// Range add, range min. `T::default()` is assumed to be zero.
+///
+/// `T::MAX` is reserved for the identity of [`Min`], i.e. a missing element, like
+/// the elements of [`LazySegmentTree::new`]. Adds leave it unchanged, so the
+/// elements must stay below `T::MAX`.
+pub struct AddMin<T>(PhantomData<T>);
+
+impl<T: Bounded + Default + Add<Output = T>> LazyMap for AddMin<T> {
+    type M = Min<T>;
+    type Tag = T;
+
+    fn identity_tag() -> T {
+        T::default()
+    }
+
+    fn map(tag: &T, value: &T, _len: usize) -> T {
+        // Identity stays the identity, which also avoids overflows
+        if *value == T::MAX {
+            return *value;
+        }
+        *value + *tag
+    }
+
+    fn compose(outer: &T, inner: &T) -> T {
+        *outer + *inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    type Int = i64;
+
+    #[derive(Debug, Clone)]
+    enum Query {
+        Apply(Range<usize>, Int),
+        Compute(Range<usize>),
+    }
+
+    fn query_strategy(n: usize) -> impl Strategy<Value = Query> {
+        (any::<bool>(), 0..=n, 0..=n, -1000..=1000 as Int).prop_map(
+            |(apply, i, j, val)| {
+                let range = min(i, j)..max(i, j);
+                if apply {
+                    Query::Apply(range, val)
+                } else {
+                    Query::Compute(range)
+                }
+            },
+        )
+    }
+
+    fn arr_and_queries() -> impl Strategy<Value = (Vec<Int>, Vec<Query>)> {
+        (1..40_usize).prop_flat_map(|n| {
+            (
+                prop::collection::vec(-1000..=1000 as Int, n),
+                prop::collection::vec(query_strategy(n), 0..100),
+            )
+        })
+    }
+
+    /// Runs `queries` on both the lazy segment tree and the naive array.
+    fn check_against_naive<L: LazyMap>(
+        mut arr: Vec<Int>,
+        queries: Vec<Query>,
+        to_tag: impl Fn(Int) -> L::Tag,
+        naive_apply: impl Fn(&mut Int, Int),
+    ) -> Result<(), TestCaseError>
+    where
+        L::M: Monoid<Value = Int>,
+    {
+        let mut st = LazySegmentTree::<L>::from_slice(&arr);
+        for query in queries {
+            match query {
+                Query::Apply(range, val) => {
+                    st.apply(range.clone(), &to_tag(val));
+                    arr[range].iter_mut().for_each(|x| naive_apply(x, val));
+                }
+                Query::Compute(range) => {
+                    let expected = arr[range.clone()]
+                        .iter()
+                        .fold(L::M::identity(), |acc, x| L::M::op(&acc, x));
+                    prop_assert_eq!(st.compute(range.clone()), expected, "{:?}", range);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_sum() {
+        let mut st = LazySegmentTree::<AddSum<Int>>::new(5);
+        assert_eq!(st.compute(0..5), 0);
+        st.apply(1..4, &3);
+        st.apply(0..2, &-1);
+        // [-1, 2, 3, 3, 0]
+        assert_eq!(st.compute(0..5), 7);
+        assert_eq!(st.compute(1..3), 5);
+        assert_eq!(st.compute(4..5), 0);
+        assert_eq!(st.compute(2..2), 0);
+    }
+
+    #[test]
+    fn test_add_min() {
+        let mut st = LazySegmentTree::<AddMin<Int>>::from_slice(&[5, 1, 4, 2]);
+        assert_eq!(st.compute(0..4), 1);
+        st.apply(0..2, &10);
+        assert_eq!(st.compute(0..4), 2);
+        assert_eq!(st.compute(0..2), 11);
+        assert_eq!(st.compute(1..1), Int::MAX);
+
+        // `Int::MAX` is a missing element, adds do not change it
+        let mut st = LazySegmentTree::<AddMin<Int>>::new(3);
+        st.apply(0..3, &-10);
+        assert_eq!(st.compute(0..3), Int::MAX);
+        let mut st =
+            LazySegmentTree::<AddMin<Int>>::from_slice(&[Int::MAX, Int::MAX - 1]);
+        st.apply(0..2, &-10);
+        assert_eq!(st.compute(0..1), Int::MAX);
+        assert_eq!(st.compute(1..2), Int::MAX - 11);
+        st.apply(1..2, &11);
+        assert_eq!(st.compute(0..2), Int::MAX);
+    }
+
+    proptest! {
+        #[test]
+        fn add_sum_matches_naive((arr, queries) in arr_and_queries()) {
+            check_against_naive::<AddSum<Int>>(arr, queries, |val| val, |x, val| *x += val)?;
+        }
+
+        #[test]
+        fn assign_sum_matches_naive((arr, queries) in arr_and_queries()) {
+            check_against_naive::<AssignSum<Int>>(
+                arr,
+                queries,
+                Some,
+                |x, val| *x = val,
+            )?;
+        }
+
+        #[test]
+        fn add_min_matches_naive((arr, queries) in arr_and_queries()) {
+            check_against_naive::<AddMin<Int>>(arr, queries, |val| val, |x, val| *x += val)?;
+        }
+    }
+}
@@ -1,5 +1,20 @@
 //! Data structures
 
 pub mod disjoint_sets;
+pub mod fenwick_tree;
+pub mod implicit_treap;
+pub mod iterative_segment_tree;
+pub mod lazy_segment_tree;
+pub mod monoid;
+pub mod persistent_segment_tree;
+pub mod prefix_sum_2d;
 pub mod segment_tree;
 pub mod treap;
+
+/// Converts a length or an index into the value type of a data structure.
+fn from_usize<T: TryFrom<usize>>(val: usize) -> T {
+    let Ok(converted) = T::try_from(val) else {
+        panic!("{val} does not fit into the value type");
+    };
+    converted
+}
@@ -0,0 +1,154 @@
+//! Monoids used to parameterize data structures such as
+//! [`SegmentTree`](super::segment_tree::SegmentTree).
+
+use std::{
+    marker::PhantomData,
+    ops::{Add, BitXor, Sub},
+};
+
+type Uint = u64;
+
+/// A set of values with an associative operation and its identity element, i.e.:
+/// \forall a, b, c: op(op(a, b), c) = op(a, op(b, c)),
+/// \forall x: op(x, identity) = op(identity, x) = x
+pub trait Monoid {
+    type Value: Clone;
+
+    fn identity() -> Self::Value;
+
+    fn op(a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+/// A monoid, where every element has an inverse, i.e.:
+/// \forall x: op(x, inverse(x)) = op(inverse(x), x) = identity
+pub trait Group: Monoid {
+    fn inverse(a: &Self::Value) -> Self::Value;
+}
+
+/// Primitive integers with known minimal and maximal values.
+pub trait Bounded: Copy + Ord {
+    const MIN: Self;
+    const MAX: Self;
+}
+
+macro_rules! impl_bounded {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl Bounded for $t {
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+            }
+        )*
+    };
+}
+
+impl_bounded!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+/// Sum. `T::default()` is assumed to be zero.
+pub struct Sum<T>(PhantomData<T>);
+
+impl<T: Copy + Default + Add<Output = T>> Monoid for Sum<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::default()
+    }
+
+    fn op(a: &T, b: &T) -> T {
+        *a + *b
+    }
+}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> Group for Sum<T> {
+    fn inverse(a: &T) -> T {
+        T::default() - *a
+    }
+}
+
+/// Minimum.
+pub struct Min<T>(PhantomData<T>);
+
+impl<T: Bounded> Monoid for Min<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::MAX
+    }
+
+    fn op(a: &T, b: &T) -> T {
+        *a.min(b)
+    }
+}
+
+/// Maximum.
+pub struct Max<T>(PhantomData<T>);
+
+impl<T: Bounded> Monoid for Max<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::MIN
+    }
+
+    fn op(a: &T, b: &T) -> T {
+        *a.max(b)
+    }
+}
+
+/// Greatest common divisor, with `0` being the identity.
+pub struct Gcd;
+
+impl Monoid for Gcd {
+    type Value = Uint;
+
+    fn identity() -> Uint {
+        0
+    }
+
+    fn op(a: &Uint, b: &Uint) -> Uint {
+        if *a == 0 && *b == 0 {
+            0
+        } else {
+            crate::num::gcd(*a, *b)
+        }
+    }
+}
+
+/// Bitwise xor. `T::default()` is assumed to be zero.
+pub struct Xor<T>(PhantomData<T>);
+
+impl<T: Copy + Default + BitXor<Output = T>> Monoid for Xor<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::default()
+    }
+
+    fn op(a: &T, b: &T) -> T {
+        *a ^ *b
+    }
+}
+
+impl<T: Copy + Default + BitXor<Output = T>> Group for Xor<T> {
+    fn inverse(a: &T) -> T {
+        *a
+    }
+}
+
+/// Minimum along with its index, as `(value, idx)`.
+/// Ties are broken in favor of the smaller index.
+pub struct MinWithIndex<T>(PhantomData<T>);
+
+impl<T: Bounded> Monoid for MinWithIndex<T> {
+    type Value = (T, usize);
+
+    fn identity() -> (T, usize) {
+        (T::MAX, usize::MAX)
+    }
+
+    fn op(a: &(T, usize), b: &(T, usize)) -> (T, usize) {
+        *a.min(b)
+    }
+}
@@ -0,0 +1,274 @@
+use std::{
+    cmp::{max, min},
+    ops::Range,
+};
+
+use super::monoid::{Monoid, Sum};
+
+/// Handle of a version of a [`PersistentSegmentTree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Version(usize);
+
+/// Persistent Segment Tree over the monoid `M`. Every update copies the path
+/// from the root to the updated leaf, so it takes `O(log n)` extra memory and
+/// all previous versions stay available.
+///
+/// Nodes are stored in an arena. Node `0` is a shared empty node, which stands
+/// for a subtree of `M::identity()` values.
+///
+/// # Panics
+///
+/// May or may not panic if `n`, `range`, `idx` are out of bounds.
+pub struct PersistentSegmentTree<M: Monoid> {
+    nodes: Vec<Node<M::Value>>,
+    initial: Version,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node<V> {
+    value: V,
+    left: usize,
+    right: usize,
+}
+
+const EMPTY: usize = 0;
+
+impl<M: Monoid> PersistentSegmentTree<M> {
+    /// Returns a segment tree for `n` array elements, assumed to be all
+    /// `M::identity()`, in `O(1)`.
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        Self {
+            nodes: vec![Node {
+                value: M::identity(),
+                left: EMPTY,
+                right: EMPTY,
+            }],
+            initial: Version(EMPTY),
+            len: n,
+        }
+    }
+
+    #[must_use]
+    pub fn from_slice(arr: &[M::Value]) -> Self {
+        let mut st = Self::new(arr.len());
+        st.nodes.reserve(2 * arr.len());
+        let root = st.build_recursive(arr, 0..arr.len());
+        st.initial = Version(root);
+        st
+    }
+
+    /// Returns the version the tree was constructed with.
+    #[must_use]
+    pub fn initial(&self) -> Version {
+        self.initial
+    }
+
+    #[must_use]
+    pub fn compute(&self, version: Version, range: Range<usize>) -> M::Value {
+        self.compute_recursive(version.0, 0..self.len, range)
+    }
+
+    /// Returns a new version, where element `idx` of the given `version` is set to
+    /// `value`. The given `version` is left unchanged.
+    pub fn update(&mut self, version: Version, idx: usize, value: M::Value) -> Version {
+        Version(self.update_recursive(version.0, 0..self.len, idx, value))
+    }
+
+    fn push_node(&mut self, value: M::Value, left: usize, right: usize) -> usize {
+        self.nodes.push(Node { value, left, right });
+        self.nodes.len() - 1
+    }
+
+    fn build_recursive(&mut self, arr: &[M::Value], range: Range<usize>) -> usize {
+        match range.len() {
+            0 => EMPTY,
+            1 => self.push_node(arr[range.start].clone(), EMPTY, EMPTY),
+            _ => {
+                let middle = range.start + range.len() / 2;
+                let left = self.build_recursive(arr, range.start..middle);
+                let right = self.build_recursive(arr, middle..range.end);
+                let value = M::op(&self.nodes[left].value, &self.nodes[right].value);
+                self.push_node(value, left, right)
+            }
+        }
+    }
+
+    fn compute_recursive(
+        &self,
+        node: usize,
+        node_range: Range<usize>,
+        compute_range: Range<usize>,
+    ) -> M::Value {
+        if compute_range.is_empty() || node == EMPTY {
+            return M::identity();
+        }
+        if node_range == compute_range {
+            return self.nodes[node].value.clone();
+        }
+        let middle = node_range.start + node_range.len() / 2;
+        let left_value = self.compute_recursive(
+            self.nodes[node].left,
+            node_range.start..middle,
+            compute_range.start..min(compute_range.end, middle),
+        );
+        let right_value = self.compute_recursive(
+            self.nodes[node].right,
+            middle..node_range.end,
+            max(compute_range.start, middle)..compute_range.end,
+        );
+        M::op(&left_value, &right_value)
+    }
+
+    fn update_recursive(
+        &mut self,
+        node: usize,
+        node_range: Range<usize>,
+        idx: usize,
+        value: M::Value,
+    ) -> usize {
+        debug_assert!(!node_range.is_empty());
+        if node_range.len() == 1 {
+            return self.push_node(value, EMPTY, EMPTY);
+        }
+        let middle = node_range.start + node_range.len() / 2;
+        let (mut left, mut right) = (self.nodes[node].left, self.nodes[node].right);
+        if idx < middle {
+            left = self.update_recursive(left, node_range.start..middle, idx, value);
+        } else {
+            right = self.update_recursive(right, middle..node_range.end, idx, value);
+        }
+        let value = M::op(&self.nodes[left].value, &self.nodes[right].value);
+        self.push_node(value, left, right)
+    }
+}
+
+/// Answers "k-th smallest element in a subarray" queries in `O(log n)`.
+///
+/// Version `i` of the underlying tree counts occurrences of each (compressed)
+/// value among the first `i` elements, so the counts for `arr[l..r]` are the
+/// difference of versions `r` and `l`.
+pub struct RangeKth<T> {
+    values: Vec<T>,
+    tree: PersistentSegmentTree<Sum<usize>>,
+    versions: Vec<Version>,
+}
+
+impl<T: Ord + Clone> RangeKth<T> {
+    #[must_use]
+    pub fn new(arr: &[T]) -> Self {
+        let mut values = arr.to_vec();
+        values.sort_unstable();
+        values.dedup();
+        let mut tree = PersistentSegmentTree::<Sum<usize>>::new(values.len());
+        let mut version = tree.initial();
+        let mut versions = Vec::with_capacity(arr.len() + 1);
+        versions.push(version);
+        for elem in arr {
+            let idx = values.partition_point(|value| value < elem);
+            let cnt = tree.compute(version, idx..idx + 1);
+            version = tree.update(version, idx, cnt + 1);
+            versions.push(version);
+        }
+        Self {
+            values,
+            tree,
+            versions,
+        }
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest element of `arr[range]`,
+    /// or `None` if `k >= range.len()`.
+    #[must_use]
+    pub fn kth(&self, range: Range<usize>, k: usize) -> Option<&T> {
+        if k >= range.len() {
+            return None;
+        }
+        let (mut lo, mut hi) = (self.versions[range.start].0, self.versions[range.end].0);
+        let (mut value_range, mut k) = (0..self.values.len(), k);
+        let nodes = &self.tree.nodes;
+        while value_range.len() > 1 {
+            let middle = value_range.start + value_range.len() / 2;
+            let left_cnt = nodes[nodes[hi].left].value - nodes[nodes[lo].left].value;
+            if k < left_cnt {
+                (lo, hi) = (nodes[lo].left, nodes[hi].left);
+                value_range = value_range.start..middle;
+            } else {
+                (lo, hi) = (nodes[lo].right, nodes[hi].right);
+                value_range = middle..value_range.end;
+                k -= left_cnt;
+            }
+        }
+        Some(&self.values[value_range.start])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ds::monoid::Max;
+
+    use proptest::prelude::*;
+
+    type Int = i64;
+
+    #[test]
+    fn test_versions() {
+        let arr: [Int; 6] = [0, 2, -5, 17, -100, 44];
+        let mut st = PersistentSegmentTree::<Sum<Int>>::from_slice(&arr);
+        let mut versions = vec![(st.initial(), arr.to_vec())];
+        for (base, idx, value) in [(0, 0, 15), (1, 2, -123), (0, 5, -11), (2, 3, 8)] {
+            let (version, mut snapshot) = versions[base].clone();
+            snapshot[idx] = value;
+            versions.push((st.update(version, idx, value), snapshot));
+        }
+        for (version, snapshot) in &versions {
+            for i in 0..=snapshot.len() {
+                for j in i..=snapshot.len() {
+                    assert_eq!(st.compute(*version, i..j), snapshot[i..j].iter().sum());
+                }
+            }
+        }
+
+        let mut st = PersistentSegmentTree::<Max<Int>>::new(10);
+        let v0 = st.initial();
+        let v1 = st.update(v0, 3, 5);
+        let v2 = st.update(v1, 8, 7);
+        assert_eq!(st.compute(v0, 0..10), Int::MIN);
+        assert_eq!(st.compute(v1, 0..10), 5);
+        assert_eq!(st.compute(v2, 0..10), 7);
+        assert_eq!(st.compute(v2, 0..8), 5);
+    }
+
+    #[test]
+    fn test_kth() {
+        let arr = [5, 1, 4, 1, 3];
+        let rk = RangeKth::new(&arr);
+        assert_eq!(rk.kth(0..5, 0), Some(&1));
+        assert_eq!(rk.kth(0..5, 1), Some(&1));
+        assert_eq!(rk.kth(0..5, 2), Some(&3));
+        assert_eq!(rk.kth(0..5, 4), Some(&5));
+        assert_eq!(rk.kth(0..5, 5), None);
+        assert_eq!(rk.kth(2..4, 1), Some(&4));
+        assert_eq!(rk.kth(2..2, 0), None);
+    }
+
+    proptest! {
+        #[test]
+        fn kth_matches_sorting(
+            arr in prop::collection::vec(-20..=20 as Int, 1..40),
+            i in 0..40_usize,
+            j in 0..40_usize,
+        ) {
+            let (i, j) = (i % (arr.len() + 1), j % (arr.len() + 1));
+            let range = min(i, j)..max(i, j);
+            let rk = RangeKth::new(&arr);
+            let mut sorted = arr[range.clone()].to_vec();
+            sorted.sort_unstable();
+            for k in 0..=sorted.len() {
+                prop_assert_eq!(rk.kth(range.clone(), k), sorted.get(k));
+            }
+        }
+    }
+}
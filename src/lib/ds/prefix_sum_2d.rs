@@ -0,0 +1,148 @@
+//! Rectangle sums over a [`Vec2`] grid.
+//!
+//! Rectangles are given by two corners `(r1, c1), (r2, c2)` and are half-open,
+//! i.e. cover rows `r1..r2` and columns `c1..c2`.
+
+use std::ops::{Add, Sub};
+
+use crate::vec::Vec2;
+
+/// 2D prefix sums: `O(n * m)` construction, `O(1)` rectangle sums.
+/// `T::default()` is assumed to be zero.
+pub struct PrefixSum2d<T> {
+    /// `prefix[(r, c)]` is the sum of rows `..r` and columns `..c`.
+    prefix: Vec2<T>,
+}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> PrefixSum2d<T> {
+    #[must_use]
+    pub fn new(grid: &Vec2<T>) -> Self {
+        let (n, m) = grid.dims();
+        let mut prefix = Vec2::new((n + 1, m + 1));
+        for r in 0..n {
+            for c in 0..m {
+                prefix[(r + 1, c + 1)] =
+                    grid[(r, c)] + prefix[(r, c + 1)] + prefix[(r + 1, c)]
+                        - prefix[(r, c)];
+            }
+        }
+        Self { prefix }
+    }
+
+    /// Returns the sum of rows `r1..r2` and columns `c1..c2`.
+    #[must_use]
+    pub fn rect_sum(&self, (r1, c1): (usize, usize), (r2, c2): (usize, usize)) -> T {
+        if r1 >= r2 || c1 >= c2 {
+            return T::default();
+        }
+        self.prefix[(r2, c2)] - self.prefix[(r1, c2)] - self.prefix[(r2, c1)]
+            + self.prefix[(r1, c1)]
+    }
+}
+
+/// 2D difference array: `O(1)` rectangle add, `O(n * m)` materialization.
+/// `T::default()` is assumed to be zero.
+pub struct Difference2d<T> {
+    diff: Vec2<T>,
+}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> Difference2d<T> {
+    /// Returns a difference array for a grid of the given `dims`, assumed to be
+    /// all zeroes.
+    #[must_use]
+    pub fn new((n, m): (usize, usize)) -> Self {
+        Self {
+            diff: Vec2::new((n + 1, m + 1)),
+        }
+    }
+
+    /// Adds `delta` to every cell in rows `r1..r2` and columns `c1..c2`.
+    pub fn add_rect(
+        &mut self,
+        (r1, c1): (usize, usize),
+        (r2, c2): (usize, usize),
+        delta: T,
+    ) {
+        if r1 >= r2 || c1 >= c2 {
+            return;
+        }
+        self.diff[(r1, c1)] = self.diff[(r1, c1)] + delta;
+        self.diff[(r1, c2)] = self.diff[(r1, c2)] - delta;
+        self.diff[(r2, c1)] = self.diff[(r2, c1)] - delta;
+        self.diff[(r2, c2)] = self.diff[(r2, c2)] + delta;
+    }
+
+    /// Returns the grid with all the additions applied.
+    #[must_use]
+    pub fn materialize(&self) -> Vec2<T> {
+        let (n, m) = self.diff.dims();
+        let (n, m) = (n - 1, m - 1);
+        let mut grid: Vec2<T> = Vec2::new((n, m));
+        for r in 0..n {
+            for c in 0..m {
+                let mut value = self.diff[(r, c)];
+                if r > 0 {
+                    value = value + grid[(r - 1, c)];
+                }
+                if c > 0 {
+                    value = value + grid[(r, c - 1)];
+                }
+                if r > 0 && c > 0 {
+                    value = value - grid[(r - 1, c - 1)];
+                }
+                grid[(r, c)] = value;
+            }
+        }
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Int = i64;
+
+    fn grid_from_rows(rows: &[&[Int]]) -> Vec2<Int> {
+        let mut grid = Vec2::new((rows.len(), rows[0].len()));
+        for (r, row) in rows.iter().enumerate() {
+            for (c, &value) in row.iter().enumerate() {
+                grid[(r, c)] = value;
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn test_prefix_sum() {
+        let grid = grid_from_rows(&[&[1, 2, 3, 4], &[5, -6, 7, 8], &[9, 10, -11, 12]]);
+        let ps = PrefixSum2d::new(&grid);
+        for r1 in 0..=3 {
+            for r2 in r1..=3 {
+                for c1 in 0..=4 {
+                    for c2 in c1..=4 {
+                        let expected: Int = (r1..r2)
+                            .flat_map(|r| (c1..c2).map(move |c| (r, c)))
+                            .map(|idx| grid[idx])
+                            .sum();
+                        assert_eq!(ps.rect_sum((r1, c1), (r2, c2)), expected);
+                    }
+                }
+            }
+        }
+        assert_eq!(ps.rect_sum((2, 2), (1, 3)), 0);
+    }
+
+    #[test]
+    fn test_difference() {
+        let mut diff = Difference2d::new((3, 4));
+        diff.add_rect((0, 0), (3, 4), 1);
+        diff.add_rect((1, 1), (3, 3), 5);
+        diff.add_rect((0, 2), (1, 4), -2);
+        diff.add_rect((2, 2), (2, 4), 100);
+        assert_eq!(
+            diff.materialize(),
+            grid_from_rows(&[&[1, 1, -1, -1], &[1, 6, 6, 1], &[1, 6, 6, 1]])
+        );
+    }
+}
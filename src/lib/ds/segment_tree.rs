@@ -3,82 +3,95 @@ use std::{
     ops::Range,
 };
 
-type Int = i64;
+use super::monoid::Monoid;
 
-/// Associative operation used in the [`SegmentTree`].
-fn op(a: Int, b: Int) -> Int {
-    a + b
-}
-
-/// Zero in respect to the associative operation `op`, i.e.:  
-/// \forall x: op(x, ZERO) = op(ZERO, x) = x
-const ZERO: Int = 0;
-
-/// Segment Tree.
-///
-/// Note: Make sure `Int` is as big as you need - likely `i64`.
+/// Segment Tree over the monoid `M`, see [`super::monoid`] for ready-made ones.
 ///
 /// # Panics
 ///
 /// May or may not panic if `n`, `range`, `idx` are out of bounds.
-pub struct SegmentTree {
-    tree: Vec<Int>,
+pub struct SegmentTree<M: Monoid> {
+    tree: Vec<M::Value>,
     len: usize,
 }
 
-impl SegmentTree {
-    /// Returns a segment tree for `n` array elements, assumed to be all zeroes.
+impl<M: Monoid> SegmentTree<M> {
+    /// Returns a segment tree for `n` array elements, assumed to be all
+    /// `M::identity()`.
     #[must_use]
-    pub fn new(n: usize) -> SegmentTree {
-        SegmentTree {
-            tree: vec![ZERO; 4 * n],
+    pub fn new(n: usize) -> Self {
+        Self {
+            tree: vec![M::identity(); 4 * n],
             len: n,
         }
     }
 
     #[must_use]
-    pub fn from_slice(arr: &[Int]) -> SegmentTree {
-        let mut st = SegmentTree {
-            tree: vec![ZERO; 4 * arr.len()],
-            len: arr.len(),
-        };
+    pub fn from_slice(arr: &[M::Value]) -> Self {
+        let mut st = Self::new(arr.len());
         let root = st.root();
         Self::build_recursive(&mut st.tree, arr, &root);
         st
     }
 
     #[must_use]
-    pub fn compute(&self, range: Range<usize>) -> Int {
+    pub fn compute(&self, range: Range<usize>) -> M::Value {
         let root = self.root();
         Self::compute_recursive(&self.tree, &root, range)
     }
 
-    pub fn update(&mut self, idx: usize, value: Int) {
+    pub fn update(&mut self, idx: usize, value: M::Value) {
         let root = self.root();
         Self::update_recursive(&mut self.tree, &root, idx, value);
     }
 
-    fn build_recursive(tree: &mut [Int], arr: &[Int], node: &Node) {
+    /// Returns the largest `r`, such that `pred(compute(l..r))` holds,
+    /// assuming `pred` is monotone: `[1, 1, ... 1, 0, 0, 0]` over `r`.
+    /// `pred(M::identity())` must hold.
+    #[must_use]
+    pub fn max_right(&self, l: usize, pred: impl Fn(&M::Value) -> bool) -> usize {
+        debug_assert!(pred(&M::identity()));
+        let mut acc = M::identity();
+        Self::max_right_recursive(&self.tree, &self.root(), l, &pred, &mut acc)
+            .unwrap_or(self.len)
+    }
+
+    /// Returns the smallest `l`, such that `pred(compute(l..r))` holds,
+    /// assuming `pred` is monotone: `[0, 0, ... 0, 1, 1, 1]` over `l`.
+    /// `pred(M::identity())` must hold.
+    #[must_use]
+    pub fn min_left(&self, r: usize, pred: impl Fn(&M::Value) -> bool) -> usize {
+        debug_assert!(pred(&M::identity()));
+        let mut acc = M::identity();
+        Self::min_left_recursive(&self.tree, &self.root(), r, &pred, &mut acc)
+            .unwrap_or(0)
+    }
+
+    fn build_recursive(tree: &mut [M::Value], arr: &[M::Value], node: &Node) {
         match node.range.len() {
             0 => (),
             1 => {
-                tree[node.id] = arr[node.range.start];
+                tree[node.id] = arr[node.range.start].clone();
             }
             _ => {
                 let (left_node, right_node) = node.children();
                 Self::build_recursive(tree, arr, &left_node);
                 Self::build_recursive(tree, arr, &right_node);
-                tree[node.id] = op(tree[left_node.id], tree[right_node.id]);
+                tree[node.id] = M::op(&tree[left_node.id], &tree[right_node.id]);
             }
         }
     }
 
-    fn compute_recursive(tree: &[Int], node: &Node, compute_range: Range<usize>) -> Int {
+    fn compute_recursive(
+        tree: &[M::Value],
+        node: &Node,
+        compute_range: Range<usize>,
+    ) -> M::Value {
         if compute_range.is_empty() {
-            return ZERO;
+            return M::identity();
         }
         if node.range == compute_range {
-            return tree[node.id];
+            return tree[node.id].clone();
         }
         let (left_node, right_node) = node.children();
         let left_compute_range =
@@ -87,10 +100,10 @@ impl SegmentTree {
             max(compute_range.start, right_node.range.start)..compute_range.end;
         let left_value = Self::compute_recursive(tree, &left_node, left_compute_range);
         let right_value = Self::compute_recursive(tree, &right_node, right_compute_range);
-        op(left_value, right_value)
+        M::op(&left_value, &right_value)
     }
 
-    fn update_recursive(tree: &mut [Int], node: &Node, idx: usize, value: Int) {
+    fn update_recursive(tree: &mut [M::Value], node: &Node, idx: usize, value: M::Value) {
         debug_assert!(!node.range.is_empty());
         if node.range.len() == 1 {
             tree[node.id] = value;
@@ -102,25 +115,86 @@ impl SegmentTree {
         } else {
             Self::update_recursive(tree, &right_node, idx, value);
         }
-        tree[node.id] = op(tree[left_node.id], tree[right_node.id]);
+        tree[node.id] = M::op(&tree[left_node.id], &tree[right_node.id]);
     }
 
-    fn root(&self) -> Node {
-        Node {
-            id: 1,
-            range: 0..self.len,
+    /// Accumulates the node's part of `l..` into `acc` while `pred` holds.
+    /// Returns the first index, at which `pred` breaks, if it is in the node.
+    fn max_right_recursive(
+        tree: &[M::Value],
+        node: &Node,
+        l: usize,
+        pred: &impl Fn(&M::Value) -> bool,
+        acc: &mut M::Value,
+    ) -> Option<usize> {
+        if node.range.is_empty() || node.range.end <= l {
+            return None;
+        }
+        if l <= node.range.start {
+            let new_acc = M::op(acc, &tree[node.id]);
+            if pred(&new_acc) {
+                *acc = new_acc;
+                return None;
+            }
+            if node.range.len() == 1 {
+                return Some(node.range.start);
+            }
         }
+        let (left_node, right_node) = node.children();
+        Self::max_right_recursive(tree, &left_node, l, pred, acc)
+            .or_else(|| Self::max_right_recursive(tree, &right_node, l, pred, acc))
+    }
+
+    /// Accumulates the node's part of `..r` into `acc` (from right to left)
+    /// while `pred` holds. Returns the index right after the element, at which
+    /// `pred` breaks, if it is in the node.
+    fn min_left_recursive(
+        tree: &[M::Value],
+        node: &Node,
+        r: usize,
+        pred: &impl Fn(&M::Value) -> bool,
+        acc: &mut M::Value,
+    ) -> Option<usize> {
+        if node.range.is_empty() || r <= node.range.start {
+            return None;
+        }
+        if node.range.end <= r {
+            let new_acc = M::op(&tree[node.id], acc);
+            if pred(&new_acc) {
+                *acc = new_acc;
+                return None;
+            }
+            if node.range.len() == 1 {
+                return Some(node.range.end);
+            }
+        }
+        let (left_node, right_node) = node.children();
+        Self::min_left_recursive(tree, &right_node, r, pred, acc)
+            .or_else(|| Self::min_left_recursive(tree, &left_node, r, pred, acc))
+    }
+
+    fn root(&self) -> Node {
+        Node::root(self.len)
     }
 }
 
+/// Node of a recursive segment tree: its id in the tree array and the array range
+/// it covers. Children of node `id` are `2 * id` and `2 * id + 1`.
 #[derive(Debug)]
-struct Node {
-    id: usize,
-    range: Range<usize>,
+pub(super) struct Node {
+    pub(super) id: usize,
+    pub(super) range: Range<usize>,
 }
 
 impl Node {
-    fn children(&self) -> (Node, Node) {
+    pub(super) fn root(len: usize) -> Node {
+        Node {
+            id: 1,
+            range: 0..len,
+        }
+    }
+
+    pub(super) fn children(&self) -> (Node, Node) {
         let middle = self.range.start + self.range.len() / 2;
         (
             Node {
@@ -138,9 +212,19 @@ impl Node {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ds::monoid::{Gcd, Max, Min, MinWithIndex, Sum, Xor};
 
-    fn update_and_check(arr: &mut [Int], st: &mut SegmentTree, idx: usize, value: Int) {
-        arr[idx] = value;
+    type Int = i64;
+
+    fn update_and_check<M: Monoid>(
+        arr: &mut [M::Value],
+        st: &mut SegmentTree<M>,
+        idx: usize,
+        value: M::Value,
+    ) where
+        M::Value: PartialEq + std::fmt::Debug,
+    {
+        arr[idx] = value.clone();
         st.update(idx, value);
         check_st(arr, st);
     }
@@ -148,7 +232,7 @@ mod tests {
     #[test]
     fn test() {
         let mut arr = [0, 2, -5, 17, -100, 44];
-        let mut st = SegmentTree::from_slice(&arr);
+        let mut st = SegmentTree::<Sum<Int>>::from_slice(&arr);
         check_st(&arr, &st);
         update_and_check(&mut arr, &mut st, 0, 15);
         update_and_check(&mut arr, &mut st, 2, -123);
@@ -163,25 +247,102 @@ mod tests {
     fn test_trivial() {
         const ST_LEN: usize = 5;
         let arr = [0; ST_LEN];
-        let st = SegmentTree::new(ST_LEN);
+        let st = SegmentTree::<Sum<Int>>::new(ST_LEN);
         check_st(&arr, &st);
-        let st = SegmentTree::new(1);
+        let st = SegmentTree::<Sum<Int>>::new(1);
         check_st(&arr[..1], &st);
     }
 
+    #[test]
+    fn test_monoids() {
+        let mut arr = [7, 2, -5, 17, -100, 44, 2];
+        let mut st = SegmentTree::<Min<Int>>::from_slice(&arr);
+        check_st(&arr, &st);
+        update_and_check(&mut arr, &mut st, 4, 100);
+        update_and_check(&mut arr, &mut st, 0, -6);
+
+        let mut arr = [7, 2, -5, 17, -100, 44, 2];
+        let mut st = SegmentTree::<Max<Int>>::from_slice(&arr);
+        check_st(&arr, &st);
+        update_and_check(&mut arr, &mut st, 5, -1);
+        update_and_check(&mut arr, &mut st, 6, 18);
+
+        let mut arr = [12, 18, 0, 30, 7, 49, 14];
+        let mut st = SegmentTree::<Gcd>::from_slice(&arr);
+        check_st(&arr, &st);
+        update_and_check(&mut arr, &mut st, 4, 0);
+        update_and_check(&mut arr, &mut st, 0, 42);
+
+        let mut arr = [0b1010_u32, 0b0110, 0b1111, 0, 0b0001];
+        let mut st = SegmentTree::<Xor<u32>>::from_slice(&arr);
+        check_st(&arr, &st);
+        update_and_check(&mut arr, &mut st, 3, 0b1000);
+
+        let mut arr = [(3, 0), (1, 1), (4, 2), (1, 3), (5, 4), (9, 5)];
+        let mut st = SegmentTree::<MinWithIndex<Int>>::from_slice(&arr);
+        check_st(&arr, &st);
+        assert_eq!(st.compute(0..6), (1, 1));
+        assert_eq!(st.compute(2..6), (1, 3));
+        update_and_check(&mut arr, &mut st, 1, (2, 1));
+        assert_eq!(st.compute(0..6), (1, 3));
+    }
+
+    #[test]
+    fn test_max_right_min_left() {
+        let arr: [Int; 8] = [3, 1, 4, 1, 5, 9, 2, 6];
+        let st = SegmentTree::<Sum<Int>>::from_slice(&arr);
+        for l in 0..=arr.len() {
+            for k in 0..40 {
+                let expected = (l..=arr.len())
+                    .take_while(|&r| arr[l..r].iter().sum::<Int>() <= k)
+                    .last()
+                    .unwrap();
+                assert_eq!(
+                    st.max_right(l, |&sum| sum <= k),
+                    expected,
+                    "l = {l}, k = {k}"
+                );
+            }
+        }
+        for r in 0..=arr.len() {
+            for k in 0..40 {
+                let expected = (0..=r)
+                    .rev()
+                    .take_while(|&l| arr[l..r].iter().sum::<Int>() <= k)
+                    .last()
+                    .unwrap();
+                assert_eq!(
+                    st.min_left(r, |&sum| sum <= k),
+                    expected,
+                    "r = {r}, k = {k}"
+                );
+            }
+        }
+
+        let st = SegmentTree::<Max<Int>>::from_slice(&arr);
+        // First index in `2..` with value greater than 4
+        assert_eq!(st.max_right(2, |&mx| mx <= 4), 4);
+        assert_eq!(st.max_right(0, |&mx| mx <= 100), arr.len());
+        assert_eq!(st.min_left(8, |&mx| mx < 9), 6);
+
+        let st = SegmentTree::<Sum<Int>>::new(0);
+        assert_eq!(st.max_right(0, |&sum| sum <= 0), 0);
+        assert_eq!(st.min_left(0, |&sum| sum <= 0), 0);
+    }
+
     #[allow(clippy::reversed_empty_ranges)]
-    fn check_st(arr: &[Int], st: &SegmentTree) {
+    fn check_st<M: Monoid>(arr: &[M::Value], st: &SegmentTree<M>)
+    where
+        M::Value: PartialEq + std::fmt::Debug,
+    {
         for i in 0..arr.len() {
             for j in i..arr.len() {
-                assert_eq!(
-                    st.compute(i..j),
-                    arr[i..j].iter().sum(),
-                    "i = {}, j = {}",
-                    i,
-                    j
-                );
+                let expected = arr[i..j]
+                    .iter()
+                    .fold(M::identity(), |acc, x| M::op(&acc, x));
+                assert_eq!(st.compute(i..j), expected, "i = {i}, j = {j}");
             }
         }
-        assert_eq!(st.compute(1..0), 0);
+        assert_eq!(st.compute(1..0), M::identity());
     }
 }
@@ -1,15 +1,80 @@
-use std::cmp::Ordering;
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    ops::{Bound, Index, RangeBounds, RangeFull},
+};
+
+use super::monoid::Monoid;
+use crate::rng::Rng;
 
 /// Cartesian tree.
 /// BST on keys K, min-heap on weights W.
+/// Optionally maintains aggregates of values V over the monoid M, see [`Fold`].
+pub struct Treap<K, V, W, M: Fold<V> = NoFold> {
+    root: NodePtr<K, V, W, M>,
+}
+
+type NodePtr<K, V, W, M> = Option<Box<Node<K, V, W, M>>>;
+
+/// Monoid over the values `V` of a [`Treap`], whose aggregate is maintained for
+/// every subtree. Any [`Monoid`] with `Value = V` is one.
+pub trait Fold<V> {
+    type Value: Clone;
+
+    fn identity() -> Self::Value;
+
+    fn op(a: &Self::Value, b: &Self::Value) -> Self::Value;
+
+    fn lift(value: &V) -> Self::Value;
+}
+
+impl<M: Monoid> Fold<M::Value> for M {
+    type Value = M::Value;
+
+    fn identity() -> M::Value {
+        M::identity()
+    }
+
+    fn op(a: &M::Value, b: &M::Value) -> M::Value {
+        M::op(a, b)
+    }
+
+    fn lift(value: &M::Value) -> M::Value {
+        value.clone()
+    }
+}
+
+/// No aggregates, the default for [`Treap`].
 #[derive(Debug)]
-pub struct Treap<K, V, W> {
-    root: NodePtr<K, V, W>,
+pub struct NoFold;
+
+impl<V> Fold<V> for NoFold {
+    type Value = ();
+
+    fn identity() {}
+
+    fn op((): &(), (): &()) {}
+
+    fn lift(_value: &V) {}
 }
 
-type NodePtr<K, V, W> = Option<Box<Node<K, V, W>>>;
+fn after_start<K: Ord>(key: &K, start: Bound<&K>) -> bool {
+    match start {
+        Bound::Included(start) => start <= key,
+        Bound::Excluded(start) => start < key,
+        Bound::Unbounded => true,
+    }
+}
 
-impl<K: Ord, V, W: Ord> Treap<K, V, W> {
+fn before_end<K: Ord>(key: &K, end: Bound<&K>) -> bool {
+    match end {
+        Bound::Included(end) => key <= end,
+        Bound::Excluded(end) => key < end,
+        Bound::Unbounded => true,
+    }
+}
+
+impl<K: Ord, V, W: Ord, M: Fold<V>> Treap<K, V, W, M> {
     #[must_use]
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
@@ -30,103 +95,415 @@ impl<K: Ord, V, W: Ord> Treap<K, V, W> {
         key_node.map(|n| (n.key, n.value))
     }
 
+    /// Splits the treap in two at `key`: `self` keeps the items with keys less
+    /// than `key`, and the rest are returned.
+    #[must_use]
+    pub fn split_off(&mut self, key: &K) -> Self {
+        let (left_node, key_node, right_node) = Node::split_by(self.root.take(), key);
+        self.root = left_node;
+        Self {
+            root: Node::merge(key_node, right_node),
+        }
+    }
+
+    /// Moves all items of `other` to `self` in `O(log n)`, leaving `other` empty.
+    /// All keys of `other` must be greater than the keys of `self`.
+    pub fn append(&mut self, other: &mut Self) {
+        debug_assert!(match (self.max(), other.min()) {
+            (Some((max_key, _)), Some((min_key, _))) => max_key < min_key,
+            _ => true,
+        });
+        self.root = Node::merge(self.root.take(), other.root.take());
+    }
+
+    /// Moves all items of `other` to `self`, leaving `other` empty. On equal keys
+    /// the value from `other` is kept.
+    ///
+    /// Takes `O(m log(n / m))`, where `m <= n` are the sizes of the treaps, so
+    /// merging sets small-to-large is `O(n log n)` in total.
+    pub fn union(&mut self, other: &mut Self) {
+        self.root = Node::union(self.root.take(), other.root.take());
+    }
+
     #[must_use]
     pub fn find_greater_or_equal(&self, search_key: &K) -> Option<(&K, &V)> {
         Node::find_greater_or_equal(self.root.as_deref(), search_key)
             .map(|n| (&n.key, &n.value))
     }
 
+    #[must_use]
+    pub fn find_less_or_equal(&self, search_key: &K) -> Option<(&K, &V)> {
+        Node::find_less_or_equal(self.root.as_deref(), search_key)
+            .map(|n| (&n.key, &n.value))
+    }
+
     #[must_use]
     /// Returns how many items are in the treap with keys less than `search_key`
     pub fn count_less(&self, search_key: &K) -> usize {
         Node::count_less(self.root.as_deref(), search_key)
     }
+
+    /// Returns how many items are in the treap with keys in `range`
+    #[must_use]
+    pub fn count_in_range(&self, range: impl RangeBounds<K>) -> usize {
+        let count_less_or_equal =
+            |key: &K| self.count_less(key) + usize::from(self.contains_key(key));
+        let before_start = match range.start_bound() {
+            Bound::Included(key) => self.count_less(key),
+            Bound::Excluded(key) => count_less_or_equal(key),
+            Bound::Unbounded => 0,
+        };
+        let up_to_end = match range.end_bound() {
+            Bound::Included(key) => count_less_or_equal(key),
+            Bound::Excluded(key) => self.count_less(key),
+            Bound::Unbounded => self.len(),
+        };
+        up_to_end.saturating_sub(before_start)
+    }
+
+    /// Returns the aggregate of the values with keys in `range`
+    #[must_use]
+    pub fn fold(&self, range: impl RangeBounds<K>) -> M::Value {
+        Node::fold(self.root.as_deref(), &range)
+    }
+
+    #[must_use]
+    pub fn get(&self, key: &K) -> Option<&V> {
+        Node::find(self.root.as_deref(), key).map(|n| &n.value)
+    }
+
+    #[must_use]
+    pub fn contains_key(&self, key: &K) -> bool {
+        Node::find(self.root.as_deref(), key).is_some()
+    }
+
+    /// Returns the `i`-th (0-indexed) item in the order of keys
+    #[must_use]
+    pub fn kth(&self, mut i: usize) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref();
+        while let Some(n) = node {
+            let left_size = Node::size(n.left.as_deref());
+            match i.cmp(&left_size) {
+                Ordering::Equal => return Some((&n.key, &n.value)),
+                Ordering::Less => node = n.left.as_deref(),
+                Ordering::Greater => {
+                    i -= left_size + 1;
+                    node = n.right.as_deref();
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the item with the smallest key
+    #[must_use]
+    pub fn min(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Returns the item with the largest key
+    #[must_use]
+    pub fn max(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        Node::size(self.root.as_deref())
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Iterates over the items in the order of keys
+    #[must_use]
+    pub fn iter(&self) -> Iter<'_, K, V, W, M> {
+        self.range(..)
+    }
+
+    /// Iterates over the items with keys in `range` in the order of keys
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, W, R, M> {
+        let mut stack = Vec::new();
+        let mut node = self.root.as_deref();
+        while let Some(n) = node {
+            if after_start(&n.key, range.start_bound()) {
+                stack.push(n);
+                node = n.left.as_deref();
+            } else {
+                node = n.right.as_deref();
+            }
+        }
+        Range { stack, range }
+    }
 }
 
-#[derive(Debug)]
-struct Node<K, V, W> {
-    key: K,
-    value: V,
-    weight: W,
-    left: NodePtr<K, V, W>,
-    right: NodePtr<K, V, W>,
-    size: usize,
+impl<K: Ord, V, W: Ord> Treap<K, V, W> {
+    /// Only available without aggregates, which would get out of date.
+    #[must_use]
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut node = self.root.as_deref_mut();
+        while let Some(n) = node {
+            match key.cmp(&n.key) {
+                Ordering::Equal => return Some(&mut n.value),
+                Ordering::Less => node = n.left.as_deref_mut(),
+                Ordering::Greater => node = n.right.as_deref_mut(),
+            }
+        }
+        None
+    }
 }
 
-impl<K: Ord, V, W: Ord> Node<K, V, W> {
-    pub fn new(key: K, value: V, weight: W) -> Self {
-        Self {
-            key,
-            value,
-            weight,
-            left: None,
-            right: None,
-            size: 1,
+impl<K: Ord + Debug, V: Debug, W: Ord, M: Fold<V>> Debug for Treap<K, V, W, M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V, W: Ord, M: Fold<V>> Index<&K> for Treap<K, V, W, M> {
+    type Output = V;
+
+    fn index(&self, key: &K) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+/// Iterator over the items of a [`Treap`] with keys in the given range.
+pub struct Range<'a, K, V, W, R, M: Fold<V> = NoFold> {
+    /// Nodes, whose left subtrees have been visited, but they themselves haven't.
+    stack: Vec<&'a Node<K, V, W, M>>,
+    range: R,
+}
+
+/// Iterator over all the items of a [`Treap`].
+pub type Iter<'a, K, V, W, M = NoFold> = Range<'a, K, V, W, RangeFull, M>;
+
+impl<'a, K: Ord, V, W, R: RangeBounds<K>, M: Fold<V>> Iterator
+    for Range<'a, K, V, W, R, M>
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if !before_end(&node.key, self.range.end_bound()) {
+            self.stack.clear();
+            return None;
+        }
+        let mut next = node.right.as_deref();
+        while let Some(n) = next {
+            self.stack.push(n);
+            next = n.left.as_deref();
         }
+        Some((&node.key, &node.value))
     }
-    /// Splits this treap into 3 treaps: `("< k", "= k", "> k")`.
+}
+
+impl<'a, K: Ord, V, W: Ord, M: Fold<V>> IntoIterator for &'a Treap<K, V, W, M> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, W, M>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Owning iterator over the items of a [`Treap`] in the order of keys.
+pub struct IntoIter<K, V, W, M: Fold<V> = NoFold> {
+    stack: Vec<Box<Node<K, V, W, M>>>,
+}
+
+impl<K, V, W, M: Fold<V>> IntoIter<K, V, W, M> {
+    fn push_left_path(&mut self, mut node: NodePtr<K, V, W, M>) {
+        while let Some(mut n) = node {
+            node = n.left.take();
+            self.stack.push(n);
+        }
+    }
+}
+
+impl<K, V, W, M: Fold<V>> Iterator for IntoIter<K, V, W, M> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?;
+        self.push_left_path(node.right.take());
+        Some((node.key, node.value))
+    }
+}
+
+impl<K, V, W, M: Fold<V>> IntoIterator for Treap<K, V, W, M> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, W, M>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut iter = IntoIter { stack: Vec::new() };
+        iter.push_left_path(self.root);
+        iter
+    }
+}
+
+/// Node of a treap. Split and merge are shared by [`Treap`] and
+/// [`ImplicitTreap`](super::implicit_treap::ImplicitTreap), which differ in how a
+/// split point is located and in what a node maintains.
+pub(super) trait TreapNode: Sized {
+    type Weight: Ord;
+
+    fn weight(&self) -> &Self::Weight;
+
+    fn left(&mut self) -> &mut Option<Box<Self>>;
+
+    fn right(&mut self) -> &mut Option<Box<Self>>;
+
+    /// Pushes pending lazy updates down to the children.
+    fn push(&mut self) {}
+
+    /// Recomputes the size and the aggregates of the subtree from the children.
+    /// The node must be pushed.
+    fn update(&mut self);
+
+    /// Splits this treap into 3 treaps: the nodes before the split point, the node
+    /// at it (if any) and the nodes after it. `locate` is called on the nodes on
+    /// the way down and tells where the split point is relative to the node.
     #[allow(clippy::type_complexity)]
-    fn split_by(
-        node: Option<Box<Node<K, V, W>>>,
-        split_key: &K,
-    ) -> (NodePtr<K, V, W>, NodePtr<K, V, W>, NodePtr<K, V, W>) {
+    fn split<F: FnMut(&Self) -> Ordering>(
+        node: Option<Box<Self>>,
+        locate: &mut F,
+    ) -> (Option<Box<Self>>, Option<Box<Self>>, Option<Box<Self>>) {
         let Some(mut node) = node else {
             return (None, None, None);
         };
-        match split_key.cmp(&node.key) {
+        node.push();
+        match locate(&node) {
             Ordering::Equal => {
-                let (left_tree, right_tree) = (node.left.take(), node.right.take());
-                node.update_size();
+                let (left_tree, right_tree) = (node.left().take(), node.right().take());
+                node.update();
                 (left_tree, Some(node), right_tree)
             }
             Ordering::Less => {
                 let (left_tree, split_node, right_tree) =
-                    Node::split_by(node.left.take(), split_key);
-                node.left = right_tree;
-                node.update_size();
+                    Self::split(node.left().take(), locate);
+                *node.left() = right_tree;
+                node.update();
                 (left_tree, split_node, Some(node))
             }
             Ordering::Greater => {
                 let (left_tree, split_node, right_tree) =
-                    Node::split_by(node.right.take(), split_key);
-                node.right = left_tree;
-                node.update_size();
+                    Self::split(node.right().take(), locate);
+                *node.right() = left_tree;
+                node.update();
                 (Some(node), split_node, right_tree)
             }
         }
     }
 
+    /// Concatenates two treaps: all nodes of `left_node` go before `right_node`.
     fn merge(
-        left_node: NodePtr<K, V, W>,
-        right_node: NodePtr<K, V, W>,
-    ) -> NodePtr<K, V, W> {
+        left_node: Option<Box<Self>>,
+        right_node: Option<Box<Self>>,
+    ) -> Option<Box<Self>> {
         let Some(mut left_node) = left_node else {
             return right_node;
         };
         let Some(mut right_node) = right_node else {
             return Some(left_node);
         };
-        match left_node.weight.cmp(&right_node.weight) {
-            Ordering::Less => {
-                left_node.right = Node::merge(left_node.right.take(), Some(right_node));
-                left_node.update_size();
-                Some(left_node)
-            }
-            Ordering::Equal | Ordering::Greater => {
-                right_node.left = Node::merge(Some(left_node), right_node.left.take());
-                right_node.update_size();
-                Some(right_node)
+        if left_node.weight() < right_node.weight() {
+            left_node.push();
+            *left_node.right() = Self::merge(left_node.right().take(), Some(right_node));
+            left_node.update();
+            Some(left_node)
+        } else {
+            right_node.push();
+            *right_node.left() = Self::merge(Some(left_node), right_node.left().take());
+            right_node.update();
+            Some(right_node)
+        }
+    }
+}
+
+struct Node<K, V, W, M: Fold<V>> {
+    key: K,
+    value: V,
+    weight: W,
+    left: NodePtr<K, V, W, M>,
+    right: NodePtr<K, V, W, M>,
+    size: usize,
+    /// Aggregate of the values in the subtree.
+    agg: M::Value,
+}
+
+impl<K: Ord, V, W: Ord, M: Fold<V>> Node<K, V, W, M> {
+    pub fn new(key: K, value: V, weight: W) -> Self {
+        Self {
+            agg: M::lift(&value),
+            key,
+            value,
+            weight,
+            left: None,
+            right: None,
+            size: 1,
+        }
+    }
+
+    /// Splits this treap into 3 treaps: `("< k", "= k", "> k")`.
+    #[allow(clippy::type_complexity)]
+    fn split_by(
+        node: NodePtr<K, V, W, M>,
+        split_key: &K,
+    ) -> (
+        NodePtr<K, V, W, M>,
+        NodePtr<K, V, W, M>,
+        NodePtr<K, V, W, M>,
+    ) {
+        Node::split(node, &mut |node: &Self| split_key.cmp(&node.key))
+    }
+
+    /// Merges two treaps with arbitrary keys. On equal keys the value from
+    /// `right_node` is kept.
+    fn union(
+        left_node: NodePtr<K, V, W, M>,
+        right_node: NodePtr<K, V, W, M>,
+    ) -> NodePtr<K, V, W, M> {
+        let Some(mut left_node) = left_node else {
+            return right_node;
+        };
+        let Some(mut right_node) = right_node else {
+            return Some(left_node);
+        };
+        if right_node.weight < left_node.weight {
+            let (left_tree, _, right_tree) =
+                Node::split_by(Some(left_node), &right_node.key);
+            right_node.left = Node::union(left_tree, right_node.left.take());
+            right_node.right = Node::union(right_tree, right_node.right.take());
+            right_node.update();
+            Some(right_node)
+        } else {
+            let (left_tree, key_node, right_tree) =
+                Node::split_by(Some(right_node), &left_node.key);
+            if let Some(key_node) = key_node {
+                left_node.value = key_node.value;
             }
+            left_node.left = Node::union(left_node.left.take(), left_tree);
+            left_node.right = Node::union(left_node.right.take(), right_tree);
+            left_node.update();
+            Some(left_node)
         }
     }
 
     fn find_greater_or_equal<'n>(
-        node: Option<&'n Node<K, V, W>>,
+        node: Option<&'n Node<K, V, W, M>>,
         search_key: &K,
-    ) -> Option<&'n Node<K, V, W>> {
-        let Some(node) = node else {
-            return None;
-        };
+    ) -> Option<&'n Node<K, V, W, M>> {
+        let node = node?;
         match search_key.cmp(&node.key) {
             Ordering::Equal => Some(node),
             Ordering::Less => {
@@ -139,16 +516,90 @@ impl<K: Ord, V, W: Ord> Node<K, V, W> {
         }
     }
 
-    fn size(node: Option<&Node<K, V, W>>) -> usize {
+    fn find_less_or_equal<'n>(
+        node: Option<&'n Node<K, V, W, M>>,
+        search_key: &K,
+    ) -> Option<&'n Node<K, V, W, M>> {
+        let node = node?;
+        match search_key.cmp(&node.key) {
+            Ordering::Equal => Some(node),
+            Ordering::Less => Node::find_less_or_equal(node.left.as_deref(), search_key),
+            Ordering::Greater => {
+                Node::find_less_or_equal(node.right.as_deref(), search_key).or(Some(node))
+            }
+        }
+    }
+
+    fn find<'n>(
+        mut node: Option<&'n Node<K, V, W, M>>,
+        search_key: &K,
+    ) -> Option<&'n Node<K, V, W, M>> {
+        while let Some(n) = node {
+            match search_key.cmp(&n.key) {
+                Ordering::Equal => return Some(n),
+                Ordering::Less => node = n.left.as_deref(),
+                Ordering::Greater => node = n.right.as_deref(),
+            }
+        }
+        None
+    }
+
+    fn size(node: Option<&Node<K, V, W, M>>) -> usize {
         node.map_or(0, |n| n.size)
     }
 
-    fn update_size(&mut self) {
-        self.size =
-            1 + Node::size(self.left.as_deref()) + Node::size(self.right.as_deref());
+    fn agg(node: Option<&Node<K, V, W, M>>) -> M::Value {
+        node.map_or_else(M::identity, |n| n.agg.clone())
     }
 
-    fn count_less(node: Option<&Node<K, V, W>>, search_key: &K) -> usize {
+    fn fold(node: Option<&Node<K, V, W, M>>, range: &impl RangeBounds<K>) -> M::Value {
+        let Some(node) = node else {
+            return M::identity();
+        };
+        if !after_start(&node.key, range.start_bound()) {
+            return Node::fold(node.right.as_deref(), range);
+        }
+        if !before_end(&node.key, range.end_bound()) {
+            return Node::fold(node.left.as_deref(), range);
+        }
+        let left_agg = Node::fold_after(node.left.as_deref(), range.start_bound());
+        let right_agg = Node::fold_before(node.right.as_deref(), range.end_bound());
+        M::op(&M::op(&left_agg, &M::lift(&node.value)), &right_agg)
+    }
+
+    /// Aggregate of the values with keys after `start`.
+    fn fold_after(node: Option<&Node<K, V, W, M>>, start: Bound<&K>) -> M::Value {
+        let Some(node) = node else {
+            return M::identity();
+        };
+        if after_start(&node.key, start) {
+            let left_agg = Node::fold_after(node.left.as_deref(), start);
+            M::op(
+                &M::op(&left_agg, &M::lift(&node.value)),
+                &Node::agg(node.right.as_deref()),
+            )
+        } else {
+            Node::fold_after(node.right.as_deref(), start)
+        }
+    }
+
+    /// Aggregate of the values with keys before `end`.
+    fn fold_before(node: Option<&Node<K, V, W, M>>, end: Bound<&K>) -> M::Value {
+        let Some(node) = node else {
+            return M::identity();
+        };
+        if before_end(&node.key, end) {
+            let right_agg = Node::fold_before(node.right.as_deref(), end);
+            M::op(
+                &M::op(&Node::agg(node.left.as_deref()), &M::lift(&node.value)),
+                &right_agg,
+            )
+        } else {
+            Node::fold_before(node.left.as_deref(), end)
+        }
+    }
+
+    fn count_less(node: Option<&Node<K, V, W, M>>, search_key: &K) -> usize {
         let Some(node) = node else { return 0 };
         match search_key.cmp(&node.key) {
             Ordering::Equal => Node::size(node.left.as_deref()),
@@ -161,44 +612,217 @@ impl<K: Ord, V, W: Ord> Node<K, V, W> {
     }
 }
 
-const RNG_SEED: u32 = 1_415_811;
+impl<K: Ord, V, W: Ord, M: Fold<V>> TreapNode for Node<K, V, W, M> {
+    type Weight = W;
+
+    fn weight(&self) -> &W {
+        &self.weight
+    }
+
+    fn left(&mut self) -> &mut NodePtr<K, V, W, M> {
+        &mut self.left
+    }
+
+    fn right(&mut self) -> &mut NodePtr<K, V, W, M> {
+        &mut self.right
+    }
+
+    fn update(&mut self) {
+        let (left, right) = (self.left.as_deref(), self.right.as_deref());
+        self.size = 1 + Node::size(left) + Node::size(right);
+        self.agg = M::op(
+            &M::op(&Node::agg(left), &M::lift(&self.value)),
+            &Node::agg(right),
+        );
+    }
+}
 
-const RNG_MULTIPLIER: u32 = 22_695_477;
-const RNG_INCREMENT: u32 = 1;
 /// Treap with random weights
-#[derive(Debug)]
-pub struct RandomTreap<K, V> {
-    treap: Treap<K, V, u32>,
-    rng_state: u32,
+pub struct RandomTreap<K, V, M: Fold<V> = NoFold> {
+    treap: Treap<K, V, u32, M>,
+    rng: Rng,
 }
 
-impl<K: Ord, V> RandomTreap<K, V> {
+impl<K: Ord, V, M: Fold<V>> RandomTreap<K, V, M> {
+    /// Returns an empty treap with weights seeded from entropy, so its shape can't
+    /// be predicted by the tests.
     #[must_use]
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
+        Self::with_rng(Rng::from_entropy())
+    }
+
+    /// Returns an empty treap, whose shape is determined by `seed`.
+    #[must_use]
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(Rng::new(seed))
+    }
+
+    fn with_rng(rng: Rng) -> Self {
         Self {
             treap: Treap::new(),
-            rng_state: RNG_SEED,
+            rng,
         }
     }
 
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        let weight = self.next_rand();
+        let weight = self.rng.next_u32();
         self.treap.insert(key, value, weight)
     }
 
-    fn next_rand(&mut self) -> u32 {
-        self.rng_state = self
-            .rng_state
-            .wrapping_mul(RNG_MULTIPLIER)
-            .wrapping_add(RNG_INCREMENT);
-        self.rng_state
+    pub fn remove(&mut self, key: &K) -> Option<(K, V)> {
+        self.treap.remove(key)
+    }
+
+    /// Splits the treap in two at `key`: `self` keeps the items with keys less
+    /// than `key`, and the rest are returned.
+    #[must_use]
+    pub fn split_off(&mut self, key: &K) -> Self {
+        Self {
+            treap: self.treap.split_off(key),
+            rng: Rng::new(self.rng.next_u64()),
+        }
+    }
+
+    /// Moves all items of `other` to `self` in `O(log n)`, leaving `other` empty.
+    /// All keys of `other` must be greater than the keys of `self`.
+    pub fn append(&mut self, other: &mut Self) {
+        self.treap.append(&mut other.treap);
+    }
+
+    /// Moves all items of `other` to `self` in `O(m log(n / m))`, leaving `other`
+    /// empty. On equal keys the value from `other` is kept.
+    pub fn union(&mut self, other: &mut Self) {
+        self.treap.union(&mut other.treap);
+    }
+
+    #[must_use]
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.treap.get(key)
+    }
+
+    /// Returns the aggregate of the values with keys in `range`
+    #[must_use]
+    pub fn fold(&self, range: impl RangeBounds<K>) -> M::Value {
+        self.treap.fold(range)
+    }
+
+    #[must_use]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.treap.contains_key(key)
+    }
+
+    #[must_use]
+    pub fn find_greater_or_equal(&self, search_key: &K) -> Option<(&K, &V)> {
+        self.treap.find_greater_or_equal(search_key)
+    }
+
+    #[must_use]
+    pub fn find_less_or_equal(&self, search_key: &K) -> Option<(&K, &V)> {
+        self.treap.find_less_or_equal(search_key)
+    }
+
+    /// Returns how many items are in the treap with keys less than `search_key`
+    #[must_use]
+    pub fn count_less(&self, search_key: &K) -> usize {
+        self.treap.count_less(search_key)
+    }
+
+    /// Returns how many items are in the treap with keys in `range`
+    #[must_use]
+    pub fn count_in_range(&self, range: impl RangeBounds<K>) -> usize {
+        self.treap.count_in_range(range)
+    }
+
+    /// Returns the `i`-th (0-indexed) item in the order of keys
+    #[must_use]
+    pub fn kth(&self, i: usize) -> Option<(&K, &V)> {
+        self.treap.kth(i)
+    }
+
+    /// Returns the item with the smallest key
+    #[must_use]
+    pub fn min(&self) -> Option<(&K, &V)> {
+        self.treap.min()
+    }
+
+    /// Returns the item with the largest key
+    #[must_use]
+    pub fn max(&self) -> Option<(&K, &V)> {
+        self.treap.max()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.treap.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.treap.is_empty()
+    }
+
+    /// Iterates over the items in the order of keys
+    #[must_use]
+    pub fn iter(&self) -> Iter<'_, K, V, u32, M> {
+        self.treap.iter()
+    }
+
+    /// Iterates over the items with keys in `range` in the order of keys
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, u32, R, M> {
+        self.treap.range(range)
+    }
+}
+
+impl<K: Ord, V> RandomTreap<K, V> {
+    /// Only available without aggregates, which would get out of date.
+    #[must_use]
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.treap.get_mut(key)
+    }
+}
+
+impl<K: Ord + Debug, V: Debug, M: Fold<V>> Debug for RandomTreap<K, V, M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.treap.fmt(f)
+    }
+}
+
+impl<K: Ord, V, M: Fold<V>> Index<&K> for RandomTreap<K, V, M> {
+    type Output = V;
+
+    fn index(&self, key: &K) -> &V {
+        &self.treap[key]
+    }
+}
+
+impl<'a, K: Ord, V, M: Fold<V>> IntoIterator for &'a RandomTreap<K, V, M> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, u32, M>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V, M: Fold<V>> IntoIterator for RandomTreap<K, V, M> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, u32, M>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.treap.into_iter()
     }
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::ds::monoid::{Max, Sum};
+
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
+
     #[test]
     fn test_basic() {
         let mut treap = Treap::<i64, u32, usize>::new();
@@ -249,4 +873,222 @@ mod tests {
         assert_eq!(treap.count_less(&0), 0);
         assert_eq!(treap.count_less(&2), 0);
     }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn test_ordered_map() {
+        let mut treap = RandomTreap::new();
+        assert!(treap.is_empty());
+        assert_eq!(treap.min(), None);
+        for (key, value) in [(5, "e"), (1, "a"), (9, "i"), (3, "c"), (7, "g")] {
+            treap.insert(key, value);
+        }
+        assert_eq!(treap.len(), 5);
+        assert_eq!(treap.get(&3), Some(&"c"));
+        assert_eq!(treap.get(&4), None);
+        assert_eq!(treap[&9], "i");
+        *treap.get_mut(&9).unwrap() = "I";
+        assert_eq!(treap[&9], "I");
+        assert_eq!(treap.kth(0), Some((&1, &"a")));
+        assert_eq!(treap.kth(3), Some((&7, &"g")));
+        assert_eq!(treap.kth(5), None);
+        assert_eq!(treap.min(), Some((&1, &"a")));
+        assert_eq!(treap.max(), Some((&9, &"I")));
+        assert_eq!(treap.find_less_or_equal(&6), Some((&5, &"e")));
+        assert_eq!(treap.find_less_or_equal(&5), Some((&5, &"e")));
+        assert_eq!(treap.find_less_or_equal(&0), None);
+        assert_eq!(treap.count_in_range(3..7), 2);
+        assert_eq!(treap.count_in_range(3..=7), 3);
+        assert_eq!(treap.count_in_range(..), 5);
+        assert_eq!(treap.count_in_range(8..2), 0);
+        assert_eq!(
+            treap.range(2..8).map(|(&k, _)| k).collect::<Vec<_>>(),
+            vec![3, 5, 7]
+        );
+        assert_eq!(treap.remove(&5), Some((5, "e")));
+        assert_eq!(
+            (&treap)
+                .into_iter()
+                .map(|(&k, &v)| (k, v))
+                .collect::<Vec<_>>(),
+            vec![(1, "a"), (3, "c"), (7, "g"), (9, "I")]
+        );
+        assert_eq!(
+            treap.into_iter().collect::<Vec<_>>(),
+            vec![(1, "a"), (3, "c"), (7, "g"), (9, "I")]
+        );
+    }
+
+    #[test]
+    fn test_fold() {
+        let mut treap = Treap::<i64, i64, usize, Max<i64>>::new();
+        for (key, value, weight) in [(2, 1, 1), (3, 11, 100), (14, 9, 150), (7, 8, 124)] {
+            treap.insert(key, value, weight);
+        }
+        treap.insert(9, 4, 1111);
+        treap.insert(833, 2, 12);
+        assert_eq!(treap.fold(..), 11);
+        assert_eq!(treap.fold(4..), 9);
+        assert_eq!(treap.fold(4..14), 8);
+        assert_eq!(treap.fold(8..=9), 4);
+        assert_eq!(treap.fold(10..14), i64::MIN);
+        treap.remove(&14);
+        assert_eq!(treap.fold(4..), 8);
+        treap.insert(7, 0, 5);
+        assert_eq!(treap.fold(4..), 4);
+    }
+
+    /// Keys and weights of the nodes in preorder, which determine the shape of a
+    /// treap.
+    fn shape<M: Fold<()>>(
+        node: Option<&Node<i32, (), u32, M>>,
+        res: &mut Vec<(i32, u32)>,
+    ) {
+        let Some(node) = node else { return };
+        res.push((node.key, node.weight));
+        shape(node.left.as_deref(), res);
+        shape(node.right.as_deref(), res);
+    }
+
+    #[test]
+    fn test_with_seed() {
+        let build = |seed| {
+            let mut treap = RandomTreap::<i32, ()>::with_seed(seed);
+            for key in 0..1000 {
+                treap.insert(key * 7 % 1000, ());
+            }
+            let mut res = Vec::new();
+            shape(treap.treap.root.as_deref(), &mut res);
+            res
+        };
+        let a = build(5);
+        assert_eq!(a.len(), 1000);
+        assert_eq!(a, build(5));
+        assert_ne!(a, build(6));
+    }
+
+    #[test]
+    fn test_split_append_union() {
+        let mut treap = Treap::<i64, u32, usize>::new();
+        for (key, weight) in [(5, 3), (1, 7), (9, 1), (3, 4), (7, 9)] {
+            treap.insert(key, 0, weight);
+        }
+        let mut tail = treap.split_off(&5);
+        assert!(treap.iter().map(|(k, _)| *k).eq([1, 3]));
+        assert!(tail.iter().map(|(k, _)| *k).eq([5, 7, 9]));
+        assert!(tail.split_off(&10).is_empty());
+        treap.append(&mut tail);
+        assert!(tail.is_empty());
+        assert!(treap.iter().map(|(k, _)| *k).eq([1, 3, 5, 7, 9]));
+
+        let mut other = Treap::new();
+        for (key, weight) in [(2, 5), (3, 0), (10, 2)] {
+            other.insert(key, 1, weight);
+        }
+        treap.union(&mut other);
+        assert!(other.is_empty());
+        assert!(treap.iter().eq([
+            (&1, &0),
+            (&2, &1),
+            (&3, &1),
+            (&5, &0),
+            (&7, &0),
+            (&9, &0),
+            (&10, &1)
+        ]));
+    }
+
+    #[derive(Debug, Clone)]
+    enum Query {
+        Insert(i32, u32),
+        Remove(i32),
+        Range(i32, i32),
+    }
+
+    fn query_strategy() -> impl Strategy<Value = Query> {
+        prop_oneof![
+            (-30..30, 0..1_000_000_u32)
+                .prop_map(|(key, value)| Query::Insert(key, value)),
+            (-30..30).prop_map(Query::Remove),
+            (-30..30, -30..30).prop_map(|(lo, hi)| Query::Range(lo, hi)),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn matches_btree_map(
+            seed in any::<u64>(),
+            queries in prop::collection::vec(query_strategy(), 0..100),
+        ) {
+            let mut treap = RandomTreap::<i32, u32, Sum<u32>>::with_seed(seed);
+            let mut map = BTreeMap::new();
+            for query in queries {
+                match query {
+                    Query::Insert(key, value) => {
+                        prop_assert_eq!(treap.insert(key, value), map.insert(key, value));
+                    }
+                    Query::Remove(key) => {
+                        prop_assert_eq!(treap.remove(&key), map.remove_entry(&key));
+                    }
+                    Query::Range(lo, hi) => {
+                        if lo > hi {
+                            prop_assert_eq!(treap.count_in_range(lo..hi), 0);
+                            continue;
+                        }
+                        prop_assert!(treap.range(lo..hi).eq(map.range(lo..hi)));
+                        prop_assert!(treap.range(lo..=hi).eq(map.range(lo..=hi)));
+                        prop_assert!(treap.range(lo..).eq(map.range(lo..)));
+                        prop_assert_eq!(treap.count_in_range(lo..=hi), map.range(lo..=hi).count());
+                        prop_assert_eq!(treap.fold(lo..hi), map.range(lo..hi).map(|(_, v)| v).sum::<u32>());
+                        prop_assert_eq!(treap.fold(lo..=hi), map.range(lo..=hi).map(|(_, v)| v).sum::<u32>());
+                        prop_assert_eq!(treap.fold(..hi), map.range(..hi).map(|(_, v)| v).sum::<u32>());
+                        prop_assert_eq!(treap.find_less_or_equal(&lo), map.range(..=lo).next_back());
+                        prop_assert_eq!(treap.find_greater_or_equal(&lo), map.range(lo..).next());
+                    }
+                }
+                prop_assert_eq!(treap.len(), map.len());
+                prop_assert_eq!(treap.fold(..), map.values().sum::<u32>());
+                prop_assert!(treap.iter().eq(map.iter()));
+                for (i, item) in map.iter().enumerate() {
+                    prop_assert_eq!(treap.kth(i), Some(item));
+                }
+                prop_assert_eq!(treap.min(), map.iter().next());
+                prop_assert_eq!(treap.max(), map.iter().next_back());
+            }
+        }
+
+        #[test]
+        fn union_matches_btree_map(
+            seed in any::<u64>(),
+            first in prop::collection::btree_map(-100..100, 0..1000_u32, 0..60),
+            second in prop::collection::btree_map(-100..100, 0..1000_u32, 0..60),
+            split_key in -110..110,
+        ) {
+            let mut treap = RandomTreap::<i32, u32, Sum<u32>>::with_seed(seed);
+            let mut other = treap.split_off(&0);
+            for (&key, &value) in &first {
+                treap.insert(key, value);
+            }
+            for (&key, &value) in &second {
+                other.insert(key, value);
+            }
+            treap.union(&mut other);
+            prop_assert!(other.is_empty());
+            let mut map = first;
+            map.extend(second);
+            prop_assert!(treap.iter().eq(map.iter()));
+            prop_assert_eq!(treap.fold(..), map.values().sum::<u32>());
+
+            let mut tail = treap.split_off(&split_key);
+            let map_tail = map.split_off(&split_key);
+            prop_assert!(treap.iter().eq(map.iter()));
+            prop_assert!(tail.iter().eq(map_tail.iter()));
+            prop_assert_eq!(tail.fold(..), map_tail.values().sum::<u32>());
+            treap.append(&mut tail);
+            prop_assert!(tail.is_empty());
+            map.extend(map_tail);
+            prop_assert!(treap.iter().eq(map.iter()));
+            prop_assert_eq!(treap.len(), map.len());
+        }
+    }
 }
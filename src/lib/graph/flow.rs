@@ -0,0 +1,228 @@
+//! Maximum flow and minimum cut.
+
+use std::collections::VecDeque;
+
+use super::Graph;
+
+pub type Flow = i64;
+
+/// Flow network. Every edge `e` is stored with its reverse edge `e ^ 1`, as built
+/// by [`Graph::add_undirected_edge`], and the residual capacity of an edge is
+/// `cap[e] - flow[e]`, where `flow[e ^ 1] == -flow[e]`.
+pub struct FlowGraph {
+    graph: Graph,
+    cap: Vec<Flow>,
+    flow: Vec<Flow>,
+}
+
+impl FlowGraph {
+    /// Initializes a network with vmax vertices and no edges, see [`Graph::new`].
+    #[must_use]
+    pub fn new(vmax: usize, emax_hint: usize) -> Self {
+        Self {
+            graph: Graph::new(vmax, 2 * emax_hint),
+            cap: Vec::with_capacity(2 * emax_hint),
+            flow: Vec::with_capacity(2 * emax_hint),
+        }
+    }
+
+    /// Returns the underlying graph, which includes the reverse edges.
+    #[must_use]
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// Adds an edge from `u` to `v` with capacity `cap` and its reverse with
+    /// capacity `rcap` (`0` for a directed edge). Returns the id `e` of the
+    /// forward edge, the reverse one is `e ^ 1`.
+    pub fn add_edge(&mut self, u: usize, v: usize, cap: Flow, rcap: Flow) -> usize {
+        let e = self.graph.num_e();
+        self.graph.add_undirected_edge(u, v);
+        self.cap.extend([cap, rcap]);
+        self.flow.extend([0, 0]);
+        e
+    }
+
+    /// Returns the current flow through edge `e`, negative if the flow goes the
+    /// other way.
+    #[must_use]
+    pub fn flow(&self, e: usize) -> Flow {
+        self.flow[e]
+    }
+
+    /// Returns the capacity of edge `e`.
+    #[must_use]
+    pub fn cap(&self, e: usize) -> Flow {
+        self.cap[e]
+    }
+
+    /// Dinic's algorithm in `O(V^2 E)`, `O(E sqrt(V))` for unit capacities, without
+    /// recursion. Pushes as much flow from `s` to `t` as possible on top of the
+    /// current flow and returns its amount. `s` and `t` must be distinct.
+    pub fn max_flow(&mut self, s: usize, t: usize) -> Flow {
+        debug_assert_ne!(s, t);
+        let mut total = 0;
+        while let Some(dist) = self.residual_dist(s, Some(t)) {
+            total += self.blocking_flow(s, t, &dist);
+        }
+        total
+    }
+
+    /// Returns the side of `s` of a minimum cut: the vertices reachable from `s`
+    /// in the residual network. Valid after [`FlowGraph::max_flow`].
+    #[must_use]
+    pub fn min_cut(&self, s: usize) -> Vec<bool> {
+        let dist = self.residual_dist(s, None).unwrap_or_default();
+        dist.iter().map(Option::is_some).collect()
+    }
+
+    /// BFS over the edges with positive residual capacity. Returns `None` if
+    /// `t` is given and unreachable.
+    fn residual_dist(&self, s: usize, t: Option<usize>) -> Option<Vec<Option<usize>>> {
+        let mut dist = vec![None; self.graph.num_v()];
+        dist[s] = Some(0);
+        let mut queue = VecDeque::from([(s, 0)]);
+        while let Some((u, dist_u)) = queue.pop_front() {
+            for (e, v) in self.graph.adj_list(u) {
+                if dist[v].is_none() && self.flow[e] < self.cap[e] {
+                    dist[v] = Some(dist_u + 1);
+                    queue.push_back((v, dist_u + 1));
+                }
+            }
+        }
+        match t {
+            Some(t) if dist[t].is_none() => None,
+            _ => Some(dist),
+        }
+    }
+
+    /// Pushes flow along shortest paths from `s` to `t` until there are none left
+    /// in the level graph `dist`, and returns its amount.
+    ///
+    /// Keeps the edges of the current path on a stack. `next_edge` is the first
+    /// edge of every vertex, which may still lead to `t`: dead ends and saturated
+    /// edges are skipped for the rest of the phase.
+    fn blocking_flow(&mut self, s: usize, t: usize, dist: &[Option<usize>]) -> Flow {
+        let mut next_edge = self.graph.first.clone();
+        let mut path: Vec<usize> = Vec::new();
+        let mut total = 0;
+        loop {
+            let u = path.last().map_or(s, |&e| self.graph.endp[e]);
+            if u == t {
+                let pushed = path
+                    .iter()
+                    .map(|&e| self.cap[e] - self.flow[e])
+                    .min()
+                    .unwrap_or_default();
+                for &e in &path {
+                    self.flow[e] += pushed;
+                    self.flow[e ^ 1] -= pushed;
+                }
+                total += pushed;
+                // Retreat to the tail of the first saturated edge
+                let saturated = path.iter().position(|&e| self.flow[e] == self.cap[e]);
+                path.truncate(saturated.unwrap_or_default());
+                continue;
+            }
+            let advanced = loop {
+                let Some(e) = next_edge[u] else {
+                    break false;
+                };
+                let head = self.graph.endp[e];
+                if self.flow[e] < self.cap[e] && dist[head] == dist[u].map(|d| d + 1) {
+                    path.push(e);
+                    break true;
+                }
+                next_edge[u] = self.graph.next[e];
+            };
+            if !advanced {
+                // Dead end, which the parent must not try again
+                let Some(e) = path.pop() else {
+                    return total;
+                };
+                next_edge[self.graph.endp[e ^ 1]] = self.graph.next[e];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_basic() {
+        // Two disjoint paths 0 -> 1 -> 3 and 0 -> 2 -> 3 and an edge 1 -> 2
+        let mut graph = FlowGraph::new(5, 5);
+        let e01 = graph.add_edge(0, 1, 3, 0);
+        let e02 = graph.add_edge(0, 2, 2, 0);
+        let e12 = graph.add_edge(1, 2, 5, 0);
+        let e13 = graph.add_edge(1, 3, 1, 0);
+        let e23 = graph.add_edge(2, 3, 4, 0);
+        assert_eq!(graph.max_flow(0, 3), 5);
+        assert_eq!(graph.max_flow(0, 3), 0);
+        assert_eq!(graph.flow(e01), 3);
+        assert_eq!(graph.flow(e02), 2);
+        assert_eq!(graph.flow(e12), 2);
+        assert_eq!(graph.flow(e13), 1);
+        assert_eq!(graph.flow(e23), 4);
+        assert_eq!(graph.flow(e23 ^ 1), -4);
+        assert_eq!(graph.min_cut(0), [true, false, false, false, false]);
+    }
+
+    #[test]
+    fn test_long_path() {
+        let n = 200_000;
+        let mut graph = FlowGraph::new(n, n - 1);
+        for u in 0..n - 1 {
+            graph.add_edge(u, u + 1, 1, 0);
+        }
+        assert_eq!(graph.max_flow(0, n - 1), 1);
+    }
+
+    proptest! {
+        #[test]
+        fn matches_brute_force_cut(
+            n in 2..8_usize,
+            edges in prop::collection::vec((0..8_usize, 0..8_usize, 0..10 as Flow, 0..3 as Flow), 0..20),
+        ) {
+            let (s, t) = (0, n - 1);
+            let mut graph = FlowGraph::new(n, edges.len());
+            let edges: Vec<_> = edges.iter().map(|&(u, v, cap, rcap)| (u % n, v % n, cap, rcap)).collect();
+            let ids: Vec<_> = edges.iter().map(|&(u, v, cap, rcap)| graph.add_edge(u, v, cap, rcap)).collect();
+            let flow = graph.max_flow(s, t);
+
+            let cut_cap = |side: &[bool]| -> Flow {
+                edges.iter().map(|&(u, v, cap, rcap)| match (side[u], side[v]) {
+                    (true, false) => cap,
+                    (false, true) => rcap,
+                    _ => 0,
+                }).sum()
+            };
+            let min_cut = (0..1_usize << n)
+                .map(|mask| (0..n).map(|u| mask >> u & 1 == 1).collect::<Vec<_>>())
+                .filter(|side| side[s] && !side[t])
+                .map(|side| cut_cap(&side))
+                .min()
+                .unwrap();
+            prop_assert_eq!(flow, min_cut);
+            let side = graph.min_cut(s);
+            prop_assert!(side[s] && !side[t]);
+            prop_assert_eq!(cut_cap(&side), flow);
+
+            let mut excess = vec![0; n];
+            for (&(u, v, cap, rcap), &e) in edges.iter().zip(&ids) {
+                prop_assert!(-rcap <= graph.flow(e) && graph.flow(e) <= cap);
+                prop_assert_eq!(graph.flow(e ^ 1), -graph.flow(e));
+                excess[u] -= graph.flow(e);
+                excess[v] += graph.flow(e);
+            }
+            for (u, &excess_u) in excess.iter().enumerate() {
+                let expected = if u == s { -flow } else if u == t { flow } else { 0 };
+                prop_assert_eq!(excess_u, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,407 @@
+//! Heavy-light decomposition: path queries on trees with segment trees.
+
+use std::{mem::swap, ops::Range};
+
+use super::{Graph, traversal::dfs};
+use crate::ds::{
+    lazy_segment_tree::{LazyMap, LazySegmentTree},
+    monoid::Monoid,
+    segment_tree::SegmentTree,
+};
+
+/// Heavy-light decomposition of a tree, built from an undirected [`Graph`] and a
+/// root.
+///
+/// Vertices are laid out in a DFS preorder, which visits the heavy child (the one
+/// with the largest subtree) first. So every heavy chain and every subtree is a
+/// contiguous range of positions, and any path crosses `O(log n)` chains.
+///
+/// # Panics
+///
+/// May or may not panic if the graph is not a tree, or a vertex is out of bounds.
+#[derive(Debug, Clone)]
+pub struct HeavyLight {
+    /// Parent of every vertex, the root is its own parent.
+    parent: Vec<usize>,
+    depth: Vec<usize>,
+    size: Vec<usize>,
+    /// Topmost vertex of the chain of every vertex.
+    head: Vec<usize>,
+    pos: Vec<usize>,
+}
+
+impl HeavyLight {
+    /// Builds the decomposition in `O(n)` without recursion. The graph must be
+    /// connected, with edges added by [`Graph::add_undirected_edge`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `root` is out of bounds, in particular if the graph is empty.
+    #[must_use]
+    pub fn new(graph: &Graph, root: usize) -> Self {
+        let n = graph.num_v();
+        let order = dfs(graph, root);
+        debug_assert_eq!(order.preorder.len(), n, "the graph is not connected");
+        let parent: Vec<usize> =
+            (0..n).map(|v| order.parent[v].unwrap_or(root)).collect();
+        let mut depth = vec![0; n];
+        for &v in &order.preorder[1..] {
+            depth[v] = depth[parent[v]] + 1;
+        }
+        let mut size = vec![1; n];
+        let mut heavy: Vec<Option<usize>> = vec![None; n];
+        for &v in &order.postorder {
+            if v == root {
+                continue;
+            }
+            let p = parent[v];
+            size[p] += size[v];
+            if heavy[p].is_none_or(|h| size[h] < size[v]) {
+                heavy[p] = Some(v);
+            }
+        }
+
+        let mut head = vec![root; n];
+        let mut pos = vec![0; n];
+        let mut stack = vec![root];
+        let mut timer = 0;
+        while let Some(v) = stack.pop() {
+            pos[v] = timer;
+            timer += 1;
+            for (_, u) in graph.adj_list(v) {
+                if u != parent[v] && Some(u) != heavy[v] {
+                    head[u] = u;
+                    stack.push(u);
+                }
+            }
+            // Pushed last, so it is visited right after `v`
+            if let Some(h) = heavy[v] {
+                head[h] = head[v];
+                stack.push(h);
+            }
+        }
+        Self {
+            parent,
+            depth,
+            size,
+            head,
+            pos,
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pos.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pos.is_empty()
+    }
+
+    /// Returns the position of `v` in the layout.
+    #[must_use]
+    pub fn pos(&self, v: usize) -> usize {
+        self.pos[v]
+    }
+
+    /// Returns the positions of the subtree of `v`.
+    #[must_use]
+    pub fn subtree_range(&self, v: usize) -> Range<usize> {
+        self.pos[v]..self.pos[v] + self.size[v]
+    }
+
+    /// Returns `O(log n)` disjoint ranges of positions, which together cover the
+    /// vertices of the path between `u` and `v`, in no particular order.
+    #[must_use]
+    pub fn path_ranges(&self, mut u: usize, mut v: usize) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        while self.head[u] != self.head[v] {
+            if self.depth[self.head[u]] < self.depth[self.head[v]] {
+                swap(&mut u, &mut v);
+            }
+            ranges.push(self.pos[self.head[u]]..self.pos[u] + 1);
+            u = self.parent[self.head[u]];
+        }
+        if self.pos[u] > self.pos[v] {
+            swap(&mut u, &mut v);
+        }
+        ranges.push(self.pos[u]..self.pos[v] + 1);
+        ranges
+    }
+
+    /// Returns the lowest common ancestor of `u` and `v` in `O(log n)`.
+    #[must_use]
+    pub fn lca(&self, mut u: usize, mut v: usize) -> usize {
+        while self.head[u] != self.head[v] {
+            if self.depth[self.head[u]] < self.depth[self.head[v]] {
+                swap(&mut u, &mut v);
+            }
+            u = self.parent[self.head[u]];
+        }
+        if self.depth[u] < self.depth[v] { u } else { v }
+    }
+
+    /// Rearranges values given for every vertex into the layout order.
+    fn layout<T: Clone>(&self, values: &[T]) -> Vec<T> {
+        let mut res = values.to_vec();
+        for (v, value) in values.iter().enumerate() {
+            res[self.pos[v]] = value.clone();
+        }
+        res
+    }
+}
+
+/// Values on the vertices of a tree: point updates and path aggregates over the
+/// monoid `M`, in `O(log^2 n)`.
+///
+/// `M` must be commutative, since the chains of a path are combined in no
+/// particular order.
+pub struct PathSegmentTree<M: Monoid> {
+    hld: HeavyLight,
+    tree: SegmentTree<M>,
+}
+
+impl<M: Monoid> PathSegmentTree<M> {
+    /// `values[v]` is the value of vertex `v`.
+    #[must_use]
+    pub fn new(graph: &Graph, root: usize, values: &[M::Value]) -> Self {
+        let hld = HeavyLight::new(graph, root);
+        let tree = SegmentTree::from_slice(&hld.layout(values));
+        Self { hld, tree }
+    }
+
+    #[must_use]
+    pub fn hld(&self) -> &HeavyLight {
+        &self.hld
+    }
+
+    /// Sets the value of vertex `v`.
+    pub fn update(&mut self, v: usize, value: M::Value) {
+        self.tree.update(self.hld.pos(v), value);
+    }
+
+    /// Returns the aggregate of the values on the path between `u` and `v`.
+    #[must_use]
+    pub fn path(&self, u: usize, v: usize) -> M::Value {
+        self.hld
+            .path_ranges(u, v)
+            .into_iter()
+            .fold(M::identity(), |acc, range| {
+                M::op(&acc, &self.tree.compute(range))
+            })
+    }
+
+    /// Returns the aggregate of the values in the subtree of `v`.
+    #[must_use]
+    pub fn subtree(&self, v: usize) -> M::Value {
+        self.tree.compute(self.hld.subtree_range(v))
+    }
+}
+
+/// Values on the vertices of a tree: path updates and path aggregates with a
+/// [`LazyMap`], in `O(log^2 n)`.
+///
+/// The monoid must be commutative, see [`PathSegmentTree`].
+pub struct LazyPathSegmentTree<L: LazyMap> {
+    hld: HeavyLight,
+    tree: LazySegmentTree<L>,
+}
+
+impl<L: LazyMap> LazyPathSegmentTree<L> {
+    /// `values[v]` is the value of vertex `v`.
+    #[must_use]
+    pub fn new(graph: &Graph, root: usize, values: &[<L::M as Monoid>::Value]) -> Self {
+        let hld = HeavyLight::new(graph, root);
+        let tree = LazySegmentTree::from_slice(&hld.layout(values));
+        Self { hld, tree }
+    }
+
+    #[must_use]
+    pub fn hld(&self) -> &HeavyLight {
+        &self.hld
+    }
+
+    /// Applies `tag` to the values on the path between `u` and `v`.
+    pub fn apply_path(&mut self, u: usize, v: usize, tag: &L::Tag) {
+        for range in self.hld.path_ranges(u, v) {
+            self.tree.apply(range, tag);
+        }
+    }
+
+    /// Applies `tag` to the values in the subtree of `v`.
+    pub fn apply_subtree(&mut self, v: usize, tag: &L::Tag) {
+        self.tree.apply(self.hld.subtree_range(v), tag);
+    }
+
+    /// Returns the aggregate of the values on the path between `u` and `v`.
+    pub fn path(&mut self, u: usize, v: usize) -> <L::M as Monoid>::Value {
+        let mut acc = L::M::identity();
+        for range in self.hld.path_ranges(u, v) {
+            acc = L::M::op(&acc, &self.tree.compute(range));
+        }
+        acc
+    }
+
+    /// Returns the aggregate of the values in the subtree of `v`.
+    pub fn subtree(&mut self, v: usize) -> <L::M as Monoid>::Value {
+        self.tree.compute(self.hld.subtree_range(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ds::{lazy_segment_tree::AddSum, monoid::Sum};
+
+    use proptest::prelude::*;
+
+    type Int = i64;
+
+    /// Vertex `i + 1` hangs below vertex `parents[i] % (i + 1)`.
+    fn build(parents: &[usize]) -> Graph {
+        let n = parents.len() + 1;
+        let mut graph = Graph::new(n, 2 * n);
+        for (i, &p) in parents.iter().enumerate() {
+            graph.add_undirected_edge(p % (i + 1), i + 1);
+        }
+        graph
+    }
+
+    /// Vertices on the path between `u` and `v` by walking up from the deeper one.
+    fn naive_path(hld: &HeavyLight, mut u: usize, mut v: usize) -> Vec<usize> {
+        let mut path = Vec::new();
+        while u != v {
+            if hld.depth[u] < hld.depth[v] {
+                swap(&mut u, &mut v);
+            }
+            path.push(u);
+            u = hld.parent[u];
+        }
+        path.push(u);
+        path
+    }
+
+    #[test]
+    fn test_basic() {
+        //     0
+        //    / \
+        //   1   2
+        //  /|   |
+        // 3 4   5
+        //       |
+        //       6
+        let graph = build(&[0, 0, 1, 1, 2, 5]);
+        let values: Vec<Int> = vec![1, 2, 3, 4, 5, 6, 7];
+        let mut st = PathSegmentTree::<Sum<Int>>::new(&graph, 0, &values);
+        assert_eq!(st.path(3, 6), 4 + 2 + 1 + 3 + 6 + 7);
+        assert_eq!(st.path(4, 4), 5);
+        assert_eq!(st.subtree(1), 2 + 4 + 5);
+        assert_eq!(st.hld().lca(4, 3), 1);
+        assert_eq!(st.hld().lca(6, 2), 2);
+        st.update(0, 100);
+        assert_eq!(st.path(4, 2), 5 + 2 + 100 + 3);
+
+        let mut st = LazyPathSegmentTree::<AddSum<Int>>::new(&graph, 0, &values);
+        st.apply_path(3, 5, &10);
+        assert_eq!(st.path(0, 6), 1 + 10 + 3 + 10 + 6 + 10 + 7);
+        st.apply_subtree(2, &-1);
+        assert_eq!(st.path(3, 6), 4 + 2 + 1 + 3 + 6 + 7 + 5 * 10 - 3);
+    }
+
+    #[derive(Debug, Clone)]
+    enum Query {
+        Update(usize, Int),
+        Path(usize, usize),
+        ApplyPath(usize, usize, Int),
+        ApplySubtree(usize, Int),
+        Subtree(usize),
+    }
+
+    fn query_strategy() -> impl Strategy<Value = Query> {
+        prop_oneof![
+            (0..40_usize, -100..=100 as Int).prop_map(|(v, x)| Query::Update(v, x)),
+            (0..40_usize, 0..40_usize).prop_map(|(u, v)| Query::Path(u, v)),
+            (0..40_usize, 0..40_usize, -100..=100 as Int)
+                .prop_map(|(u, v, x)| Query::ApplyPath(u, v, x)),
+            (0..40_usize, -100..=100 as Int).prop_map(|(v, x)| Query::ApplySubtree(v, x)),
+            (0..40_usize).prop_map(Query::Subtree),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn matches_naive(
+            parents in prop::collection::vec(0..40_usize, 0..40),
+            root in 0..40_usize,
+            queries in prop::collection::vec(query_strategy(), 0..40),
+        ) {
+            let graph = build(&parents);
+            let n = graph.num_v();
+            let root = root % n;
+            let mut values = vec![0; n];
+            let mut st = PathSegmentTree::<Sum<Int>>::new(&graph, root, &values);
+            let mut lazy = LazyPathSegmentTree::<AddSum<Int>>::new(&graph, root, &values);
+            let hld = HeavyLight::new(&graph, root);
+            // The walk between `w` and `v` ends at `v` iff `v` is an ancestor of `w`
+            let subtree = |v: usize| -> Vec<usize> {
+                (0..n).filter(|&w| naive_path(&hld, w, v).last() == Some(&v)).collect()
+            };
+
+            let mut positions: Vec<usize> = (0..n).map(|v| hld.pos(v)).collect();
+            positions.sort_unstable();
+            prop_assert!(positions.into_iter().eq(0..n));
+            for u in 0..n {
+                for v in 0..n {
+                    let path = naive_path(&hld, u, v);
+                    let ranges = hld.path_ranges(u, v);
+                    prop_assert!(ranges.len() <= 2 * (n.ilog2() as usize + 1));
+                    let mut covered: Vec<usize> = ranges.into_iter().flatten().collect();
+                    let mut expected: Vec<usize> = path.iter().map(|&w| hld.pos(w)).collect();
+                    covered.sort_unstable();
+                    expected.sort_unstable();
+                    prop_assert_eq!(covered, expected);
+                    prop_assert_eq!(hld.lca(u, v), *path.iter().min_by_key(|&&w| hld.depth[w]).unwrap());
+                }
+            }
+
+            for query in queries {
+                match query {
+                    Query::Update(v, x) => {
+                        let v = v % n;
+                        values[v] = x;
+                        st.update(v, x);
+                        let current = lazy.path(v, v);
+                        lazy.apply_path(v, v, &(x - current));
+                    }
+                    Query::Path(u, v) => {
+                        let expected: Int = naive_path(&hld, u % n, v % n).iter().map(|&w| values[w]).sum();
+                        prop_assert_eq!(st.path(u % n, v % n), expected);
+                        prop_assert_eq!(lazy.path(u % n, v % n), expected);
+                    }
+                    Query::ApplyPath(u, v, x) => {
+                        for w in naive_path(&hld, u % n, v % n) {
+                            values[w] += x;
+                            st.update(w, values[w]);
+                        }
+                        lazy.apply_path(u % n, v % n, &x);
+                    }
+                    Query::ApplySubtree(v, x) => {
+                        for w in subtree(v % n) {
+                            values[w] += x;
+                            st.update(w, values[w]);
+                        }
+                        lazy.apply_subtree(v % n, &x);
+                    }
+                    Query::Subtree(v) => {
+                        let in_subtree = subtree(v % n);
+                        let expected: Int = in_subtree.iter().map(|&w| values[w]).sum();
+                        prop_assert_eq!(hld.subtree_range(v % n).len(), in_subtree.len());
+                        prop_assert_eq!(st.subtree(v % n), expected);
+                        prop_assert_eq!(lazy.subtree(v % n), expected);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,367 @@
+//! Maximum bipartite matching and the assignment problem.
+
+use std::collections::VecDeque;
+
+use super::Graph;
+use crate::vec::Vec2;
+
+/// Matching in a bipartite graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matching {
+    pub size: usize,
+    /// Right vertex matched to every left vertex.
+    pub left_match: Vec<Option<usize>>,
+    /// Left vertex matched to every right vertex.
+    pub right_match: Vec<Option<usize>>,
+}
+
+impl Matching {
+    /// Returns the left and the right vertices of a minimum vertex cover, whose
+    /// size equals the size of the matching (König's theorem). The matching must
+    /// be maximum in `graph`.
+    ///
+    /// The cover consists of the left vertices unreachable and the right
+    /// vertices reachable by alternating paths from the unmatched left vertices.
+    #[must_use]
+    pub fn min_vertex_cover(&self, graph: &Graph) -> (Vec<usize>, Vec<usize>) {
+        let mut left_visited: Vec<bool> =
+            self.left_match.iter().map(Option::is_none).collect();
+        let mut right_visited = vec![false; self.right_match.len()];
+        let mut stack: Vec<usize> =
+            (0..graph.num_v()).filter(|&u| left_visited[u]).collect();
+        while let Some(u) = stack.pop() {
+            for (_, v) in graph.adj_list(u) {
+                if right_visited[v] {
+                    continue;
+                }
+                right_visited[v] = true;
+                if let Some(w) = self.right_match[v]
+                    && !left_visited[w]
+                {
+                    left_visited[w] = true;
+                    stack.push(w);
+                }
+            }
+        }
+        let left = (0..graph.num_v()).filter(|&u| !left_visited[u]).collect();
+        let right = (0..right_visited.len())
+            .filter(|&v| right_visited[v])
+            .collect();
+        (left, right)
+    }
+}
+
+/// Hopcroft-Karp algorithm in `O(E sqrt(V))`. The vertices of `graph` are the
+/// left part, and every edge points to a right vertex in `0..num_right`.
+#[must_use]
+pub fn hopcroft_karp(graph: &Graph, num_right: usize) -> Matching {
+    let num_left = graph.num_v();
+    let mut matching = Matching {
+        size: 0,
+        left_match: vec![None; num_left],
+        right_match: vec![None; num_right],
+    };
+    loop {
+        // Layers of the left vertices by the length of alternating paths from
+        // the unmatched ones
+        let mut dist = vec![None; num_left];
+        let mut queue = VecDeque::new();
+        for (u, dist_u) in dist.iter_mut().enumerate() {
+            if matching.left_match[u].is_none() {
+                *dist_u = Some(0);
+                queue.push_back(u);
+            }
+        }
+        // Layer of the left vertices adjacent to a free right vertex, the last one
+        // needed for the shortest augmenting paths
+        let mut free_dist = None;
+        while let Some(u) = queue.pop_front() {
+            if free_dist.is_some_and(|d| dist[u] > Some(d)) {
+                break;
+            }
+            for (_, v) in graph.adj_list(u) {
+                match matching.right_match[v] {
+                    None => free_dist = free_dist.or(dist[u]),
+                    Some(w) if dist[w].is_none() => {
+                        dist[w] = dist[u].map(|d| d + 1);
+                        queue.push_back(w);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        if free_dist.is_none() {
+            return matching;
+        }
+        let mut next_edge = graph.first.clone();
+        for u in 0..num_left {
+            if matching.left_match[u].is_none()
+                && augment(graph, u, &dist, free_dist, &mut next_edge, &mut matching)
+            {
+                matching.size += 1;
+            }
+        }
+    }
+}
+
+/// Looks for an augmenting path from `root` along the layers of `dist`, which
+/// ends at a free right vertex from layer `free_dist`, and flips it. Edges, which
+/// lead nowhere, are skipped in `next_edge` for the rest of the phase.
+///
+/// Keeps the left vertices of the current path with their edges on a stack.
+fn augment(
+    graph: &Graph,
+    root: usize,
+    dist: &[Option<usize>],
+    free_dist: Option<usize>,
+    next_edge: &mut [Option<usize>],
+    matching: &mut Matching,
+) -> bool {
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    let mut u = root;
+    loop {
+        let Some(e) = next_edge[u] else {
+            // Dead end, go back to the previous left vertex
+            let Some((prev, _)) = stack.pop() else {
+                return false;
+            };
+            u = prev;
+            continue;
+        };
+        next_edge[u] = graph.next[e];
+        let v = graph.endp[e];
+        match matching.right_match[v] {
+            None if dist[u] == free_dist => {
+                stack.push((u, e));
+                for (w, e) in stack {
+                    let v = graph.endp[e];
+                    matching.left_match[w] = Some(v);
+                    matching.right_match[v] = Some(w);
+                }
+                return true;
+            }
+            Some(w) if dist[w] == dist[u].map(|d| d + 1) => {
+                stack.push((u, e));
+                u = w;
+            }
+            _ => {}
+        }
+    }
+}
+
+pub type Cost = i64;
+
+/// Hungarian algorithm in `O(n^2 m)` for an `n x m` matrix with `n <= m`.
+/// Assigns a distinct column to every row with the minimum total cost. Returns
+/// the cost and the column of every row.
+///
+/// # Panics
+///
+/// Panics if `n > m`.
+#[must_use]
+pub fn hungarian(cost: &Vec2<Cost>) -> (Cost, Vec<usize>) {
+    let (n, m) = cost.dims();
+    assert!(n <= m, "more rows than columns");
+    // Potentials of rows and columns, `cost[(i, j)] - row[i] - col[j] >= 0` and
+    // equals zero on the assigned cells. Column `m` is a fictive one.
+    let mut row = vec![0; n];
+    let mut col = vec![0; m + 1];
+    // Row assigned to every column
+    let mut col_match: Vec<Option<usize>> = vec![None; m + 1];
+    for i in 0..n {
+        col_match[m] = Some(i);
+        let mut j0 = m;
+        let mut min_slack = vec![Cost::MAX; m + 1];
+        // Previous column on the alternating path to every column
+        let mut way = vec![m; m + 1];
+        let mut used = vec![false; m + 1];
+        // Extend the alternating tree until it reaches a free column
+        while let Some(i0) = col_match[j0] {
+            used[j0] = true;
+            let (mut delta, mut j1) = (Cost::MAX, m);
+            for j in 0..m {
+                if used[j] {
+                    continue;
+                }
+                let slack = cost[(i0, j)] - row[i0] - col[j];
+                if slack < min_slack[j] {
+                    min_slack[j] = slack;
+                    way[j] = j0;
+                }
+                if min_slack[j] < delta {
+                    delta = min_slack[j];
+                    j1 = j;
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    if let Some(i) = col_match[j] {
+                        row[i] += delta;
+                    }
+                    col[j] -= delta;
+                } else {
+                    min_slack[j] -= delta;
+                }
+            }
+            j0 = j1;
+        }
+        // Flip the alternating path
+        while j0 != m {
+            let prev = way[j0];
+            col_match[j0] = col_match[prev];
+            j0 = prev;
+        }
+    }
+    let mut assignment = vec![0; n];
+    for (j, i) in col_match[..m].iter().enumerate() {
+        if let Some(i) = *i {
+            assignment[i] = j;
+        }
+    }
+    let total = (0..n).map(|i| cost[(i, assignment[i])]).sum();
+    (total, assignment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_hopcroft_karp() {
+        let mut graph = Graph::new(4, 6);
+        for (u, v) in [(0, 0), (0, 1), (1, 0), (2, 1), (2, 2), (3, 2)] {
+            graph.add_edge(u, v);
+        }
+        let matching = hopcroft_karp(&graph, 4);
+        assert_eq!(matching.size, 3);
+        let (left, right) = matching.min_vertex_cover(&graph);
+        assert_eq!(left.len() + right.len(), 3);
+    }
+
+    #[test]
+    fn test_long_augmenting_path() {
+        // Left `u` is adjacent to right `u + 1` and `u`, in this order of
+        // `adj_list`. The greedy first phase leaves left `n - 1` unmatched, and
+        // the only augmenting path goes through the whole chain down to right `0`.
+        let n = 100_000;
+        let mut graph = Graph::new(n, 2 * n);
+        for u in 0..n {
+            graph.add_edge(u, u);
+            if u + 1 < n {
+                graph.add_edge(u, u + 1);
+            }
+        }
+        let matching = hopcroft_karp(&graph, n);
+        assert_eq!(matching.size, n);
+        assert!((0..n).all(|u| matching.left_match[u] == Some(u)));
+    }
+
+    #[test]
+    fn test_hungarian() {
+        let mut cost = Vec2::new((3, 4));
+        for (i, row) in [[4, 1, 3, 9], [2, 0, 5, 9], [3, 2, 2, 9]]
+            .iter()
+            .enumerate()
+        {
+            for (j, &c) in row.iter().enumerate() {
+                cost[(i, j)] = c;
+            }
+        }
+        assert_eq!(hungarian(&cost), (5, vec![1, 0, 2]));
+        assert_eq!(hungarian(&Vec2::new((0, 3))), (0, vec![]));
+    }
+
+    /// Maximum matching size by DP over subsets of the right part.
+    fn max_matching(n: usize, m: usize, edges: &[(usize, usize)]) -> usize {
+        let mut best = vec![None; 1 << m];
+        best[0] = Some(0);
+        for u in 0..n {
+            let mut next = best.clone();
+            for mask in 0..1 << m {
+                let Some(size) = best[mask] else { continue };
+                for &(_, v) in edges.iter().filter(|&&(a, _)| a == u) {
+                    if mask >> v & 1 == 0 {
+                        let new = &mut next[mask | 1 << v];
+                        *new = (*new).max(Some(size + 1));
+                    }
+                }
+            }
+            best = next;
+        }
+        best.into_iter().flatten().max().unwrap()
+    }
+
+    fn permutations(k: usize, m: usize) -> Vec<Vec<usize>> {
+        if k == 0 {
+            return vec![Vec::new()];
+        }
+        let mut res = Vec::new();
+        for perm in permutations(k - 1, m) {
+            for j in (0..m).filter(|j| !perm.contains(j)) {
+                let mut perm = perm.clone();
+                perm.push(j);
+                res.push(perm);
+            }
+        }
+        res
+    }
+
+    proptest! {
+        #[test]
+        fn matching_matches_brute_force(
+            n in 1..7_usize,
+            m in 1..7_usize,
+            edges in prop::collection::vec((0..7_usize, 0..7_usize), 0..20),
+        ) {
+            let edges: Vec<_> = edges.iter().map(|&(u, v)| (u % n, v % m)).collect();
+            let mut graph = Graph::new(n, edges.len());
+            for &(u, v) in &edges {
+                graph.add_edge(u, v);
+            }
+            let matching = hopcroft_karp(&graph, m);
+            prop_assert_eq!(matching.size, max_matching(n, m, &edges));
+            let matched = (0..n).filter_map(|u| matching.left_match[u].map(|v| (u, v)));
+            let mut count = 0;
+            for (u, v) in matched {
+                prop_assert!(edges.contains(&(u, v)));
+                prop_assert_eq!(matching.right_match[v], Some(u));
+                count += 1;
+            }
+            prop_assert_eq!(count, matching.size);
+
+            let (left, right) = matching.min_vertex_cover(&graph);
+            prop_assert_eq!(left.len() + right.len(), matching.size);
+            for &(u, v) in &edges {
+                prop_assert!(left.contains(&u) || right.contains(&v));
+            }
+        }
+
+        #[test]
+        fn hungarian_matches_brute_force(
+            n in 0..5_usize,
+            extra in 0..3_usize,
+            values in prop::collection::vec(-20..=20 as Cost, 35),
+        ) {
+            let m = n + extra;
+            let mut cost = Vec2::new((n, m));
+            for i in 0..n {
+                for j in 0..m {
+                    cost[(i, j)] = values[i * m + j];
+                }
+            }
+            let (total, assignment) = hungarian(&cost);
+            let expected = permutations(n, m)
+                .iter()
+                .map(|perm| (0..n).map(|i| cost[(i, perm[i])]).sum::<Cost>())
+                .min()
+                .unwrap();
+            prop_assert_eq!(total, expected);
+            let mut columns = assignment.clone();
+            columns.sort_unstable();
+            columns.dedup();
+            prop_assert_eq!(columns.len(), n);
+        }
+    }
+}
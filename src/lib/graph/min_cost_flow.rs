@@ -0,0 +1,272 @@
+//! Minimum-cost flow by successive shortest paths.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use super::{Graph, flow::Flow};
+
+/// Flow network with costs per unit of flow. Every edge `e` is stored with its
+/// reverse edge `e ^ 1` of zero capacity and the opposite cost, as built by
+/// [`Graph::add_undirected_edge`].
+///
+/// The initial network may have negative costs, but no negative cycles.
+pub struct MinCostFlowGraph {
+    graph: Graph,
+    cap: Vec<Flow>,
+    cost: Vec<Flow>,
+    flow: Vec<Flow>,
+}
+
+impl MinCostFlowGraph {
+    /// Initializes a network with vmax vertices and no edges, see [`Graph::new`].
+    #[must_use]
+    pub fn new(vmax: usize, emax_hint: usize) -> Self {
+        Self {
+            graph: Graph::new(vmax, 2 * emax_hint),
+            cap: Vec::with_capacity(2 * emax_hint),
+            cost: Vec::with_capacity(2 * emax_hint),
+            flow: Vec::with_capacity(2 * emax_hint),
+        }
+    }
+
+    /// Returns the underlying graph, which includes the reverse edges.
+    #[must_use]
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// Adds an edge from `u` to `v` with capacity `cap` and cost `cost` per unit.
+    /// Returns the id `e` of the edge, its reverse is `e ^ 1`.
+    pub fn add_edge(&mut self, u: usize, v: usize, cap: Flow, cost: Flow) -> usize {
+        let e = self.graph.num_e();
+        self.graph.add_undirected_edge(u, v);
+        self.cap.extend([cap, 0]);
+        self.cost.extend([cost, -cost]);
+        self.flow.extend([0, 0]);
+        e
+    }
+
+    /// Returns the current flow through edge `e`.
+    #[must_use]
+    pub fn flow(&self, e: usize) -> Flow {
+        self.flow[e]
+    }
+
+    /// Pushes at most `limit` units of flow from `s` to `t` on top of the current
+    /// flow, with the minimum cost. Returns the amount of flow and its cost.
+    /// Use `Flow::MAX` as `limit` for the min-cost max-flow.
+    pub fn min_cost_flow(&mut self, s: usize, t: usize, limit: Flow) -> (Flow, Flow) {
+        *self.slope(s, t, limit).last().unwrap_or(&(0, 0))
+    }
+
+    /// Like [`MinCostFlowGraph::min_cost_flow`], but returns the breakpoints of
+    /// the minimum cost as a function of the flow, which is convex and piecewise
+    /// linear: `(0, 0)`, then `(flow, cost)` at every change of the slope.
+    ///
+    /// Takes `O(F (E log V))` for the total flow `F`, plus `O(VE)` if there are
+    /// negative costs in the residual network.
+    pub fn slope(&mut self, s: usize, t: usize, limit: Flow) -> Vec<(Flow, Flow)> {
+        let mut potential = self.initial_potential(s);
+        let mut slope = vec![(0, 0)];
+        let (mut flow, mut cost) = (0, 0);
+        let mut prev_path_cost = None;
+        while flow < limit {
+            let Some(pred) = self.dijkstra(s, t, &mut potential) else {
+                break;
+            };
+            let mut path = Vec::new();
+            let mut v = t;
+            while let Some(e) = pred[v] {
+                path.push(e);
+                v = self.graph.endp[e ^ 1];
+            }
+            let Some(pushed) = path.iter().map(|&e| self.cap[e] - self.flow[e]).min()
+            else {
+                break;
+            };
+            let pushed = pushed.min(limit - flow);
+            let path_cost: Flow = path.iter().map(|&e| self.cost[e]).sum();
+            for e in path {
+                self.flow[e] += pushed;
+                self.flow[e ^ 1] -= pushed;
+            }
+            flow += pushed;
+            cost += pushed * path_cost;
+            if prev_path_cost == Some(path_cost) {
+                slope.pop();
+            }
+            slope.push((flow, cost));
+            prev_path_cost = Some(path_cost);
+        }
+        slope
+    }
+
+    fn has_residual(&self, e: usize) -> bool {
+        self.flow[e] < self.cap[e]
+    }
+
+    /// Returns potentials, which make the reduced costs of all residual edges
+    /// reachable from `s` non-negative: zeroes if there are no negative costs,
+    /// otherwise the Bellman-Ford distances from `s`.
+    fn initial_potential(&self, s: usize) -> Vec<Flow> {
+        let n = self.graph.num_v();
+        let has_negative =
+            (0..self.graph.num_e()).any(|e| self.has_residual(e) && self.cost[e] < 0);
+        if !has_negative {
+            return vec![0; n];
+        }
+        let mut dist = vec![None; n];
+        dist[s] = Some(0);
+        for _ in 0..n {
+            let mut changed = false;
+            for u in 0..n {
+                let Some(dist_u) = dist[u] else {
+                    continue;
+                };
+                for (e, v) in self.graph.adj_list(u) {
+                    let dist_v = dist_u + self.cost[e];
+                    if self.has_residual(e) && dist[v].is_none_or(|old| dist_v < old) {
+                        dist[v] = Some(dist_v);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        dist.into_iter().map(Option::unwrap_or_default).collect()
+    }
+
+    /// Dijkstra on the reduced costs, updates the potentials of the reached
+    /// vertices by their distances. Returns the edge into every vertex on a
+    /// shortest path from `s`, or `None` if `t` is unreachable.
+    fn dijkstra(
+        &self,
+        s: usize,
+        t: usize,
+        potential: &mut [Flow],
+    ) -> Option<Vec<Option<usize>>> {
+        let n = self.graph.num_v();
+        let mut dist = vec![None; n];
+        let mut pred = vec![None; n];
+        dist[s] = Some(0);
+        let mut heap = BinaryHeap::from([Reverse((0, s))]);
+        while let Some(Reverse((dist_u, u))) = heap.pop() {
+            if dist[u] != Some(dist_u) {
+                continue;
+            }
+            for (e, v) in self.graph.adj_list(u) {
+                if !self.has_residual(e) {
+                    continue;
+                }
+                let reduced = self.cost[e] + potential[u] - potential[v];
+                debug_assert!(reduced >= 0, "negative cycle");
+                let dist_v = dist_u + reduced;
+                if dist[v].is_none_or(|old| dist_v < old) {
+                    dist[v] = Some(dist_v);
+                    pred[v] = Some(e);
+                    heap.push(Reverse((dist_v, v)));
+                }
+            }
+        }
+        for (p, d) in potential.iter_mut().zip(&dist) {
+            if let Some(d) = d {
+                *p += d;
+            }
+        }
+        dist[t].map(|_| pred)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        graph::{flow::FlowGraph, shortest_paths::floyd_warshall},
+        vec::Vec2,
+    };
+
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_basic() {
+        let mut graph = MinCostFlowGraph::new(4, 5);
+        let e01 = graph.add_edge(0, 1, 2, 1);
+        let e02 = graph.add_edge(0, 2, 1, 2);
+        let e12 = graph.add_edge(1, 2, 1, -1);
+        let e13 = graph.add_edge(1, 3, 1, 6);
+        let e23 = graph.add_edge(2, 3, 2, 1);
+        assert_eq!(
+            graph.slope(0, 3, Flow::MAX),
+            [(0, 0), (1, 1), (2, 4), (3, 11)]
+        );
+        assert_eq!(graph.flow(e01), 2);
+        assert_eq!(graph.flow(e02), 1);
+        assert_eq!(graph.flow(e12), 1);
+        assert_eq!(graph.flow(e13), 1);
+        assert_eq!(graph.flow(e23), 2);
+        assert_eq!(graph.min_cost_flow(0, 3, Flow::MAX), (0, 0));
+
+        let mut graph = MinCostFlowGraph::new(3, 2);
+        graph.add_edge(0, 1, 5, 2);
+        graph.add_edge(1, 2, 5, 3);
+        assert_eq!(graph.min_cost_flow(0, 2, 3), (3, 15));
+        assert_eq!(graph.slope(0, 2, Flow::MAX), [(0, 0), (2, 10)]);
+    }
+
+    #[test]
+    fn test_negative_cost_unreachable() {
+        // Parallel edges with a negative cost, which `s` does not reach
+        let mut graph = MinCostFlowGraph::new(6, 3);
+        graph.add_edge(5, 1, 1, 0);
+        let e = graph.add_edge(5, 1, 1, -1);
+        assert_eq!(graph.slope(0, 5, 0), [(0, 0)]);
+        assert_eq!(graph.min_cost_flow(0, 5, Flow::MAX), (0, 0));
+        assert_eq!(graph.min_cost_flow(5, 1, Flow::MAX), (2, -1));
+        assert_eq!(graph.flow(e), 1);
+    }
+
+    proptest! {
+        #[test]
+        fn is_optimal(
+            n in 2..7_usize,
+            edges in prop::collection::vec((0..7_usize, 0..7_usize, 0..5 as Flow, 0..6 as Flow), 0..15),
+            potential in prop::collection::vec(0..5 as Flow, 7),
+            limit in 0..20 as Flow,
+        ) {
+            let (s, t) = (0, n - 1);
+            let mut graph = MinCostFlowGraph::new(n, edges.len());
+            let mut flow_graph = FlowGraph::new(n, edges.len());
+            let mut ids = Vec::new();
+            for &(u, v, cap, cost) in &edges {
+                let (u, v) = (u % n, v % n);
+                // Costs may be negative, but all cycles are non-negative
+                let cost = cost + potential[u] - potential[v];
+                ids.push((graph.add_edge(u, v, cap, cost), cost));
+                flow_graph.add_edge(u, v, cap, 0);
+            }
+            let slope = graph.slope(s, t, limit);
+            let (flow, cost) = *slope.last().unwrap();
+            prop_assert_eq!(flow, flow_graph.max_flow(s, t).min(limit));
+            prop_assert_eq!(cost, ids.iter().map(|&(e, c)| graph.flow(e) * c).sum::<Flow>());
+            for w in slope.windows(3) {
+                // Strictly convex breakpoints
+                let (d1, d2) = ((w[1].0 - w[0].0, w[1].1 - w[0].1), (w[2].0 - w[1].0, w[2].1 - w[1].1));
+                prop_assert!(d1.1 * d2.0 < d2.1 * d1.0);
+            }
+
+            // Optimal iff the residual network has no negative cycles
+            let mut dist = Vec2::new((n, n));
+            for u in 0..n {
+                dist[(u, u)] = Some(0);
+                for (e, v) in graph.graph().adj_list(u) {
+                    if graph.has_residual(e) && dist[(u, v)].is_none_or(|old| graph.cost[e] < old) {
+                        dist[(u, v)] = Some(graph.cost[e]);
+                    }
+                }
+            }
+            floyd_warshall(&mut dist);
+            prop_assert!((0..n).all(|u| dist[(u, u)] == Some(0)));
+        }
+    }
+}
@@ -2,6 +2,16 @@
 //! Most of the code in this module is copied from
 //! [EbTech's work](https://github.com/EbTech/rust-algorithms)
 
+pub mod flow;
+pub mod hld;
+pub mod matching;
+pub mod min_cost_flow;
+pub mod scc;
+pub mod shortest_paths;
+pub mod traversal;
+pub mod tree;
+pub mod two_sat;
+
 /// A compact graph representation. Edges are numbered in order of insertion.
 /// Each adjacency list consists of all edges pointing out from a given vertex.
 pub struct Graph {
@@ -90,6 +100,88 @@ impl<'a> Iterator for AdjListIterator<'a> {
     }
 }
 
+/// A [`Graph`] with a payload (e.g. a weight) on every edge.
+pub struct WeightedGraph<W> {
+    graph: Graph,
+    /// Maps an edge id to its payload.
+    weight: Vec<W>,
+}
+
+impl<W> WeightedGraph<W> {
+    /// Initializes a graph with vmax vertices and no edges, see [`Graph::new`].
+    #[must_use]
+    pub fn new(vmax: usize, emax_hint: usize) -> Self {
+        Self {
+            graph: Graph::new(vmax, emax_hint),
+            weight: Vec::with_capacity(emax_hint),
+        }
+    }
+
+    /// Returns the underlying unweighted graph with the same edge ids.
+    #[must_use]
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// Returns the number of vertices.
+    #[must_use]
+    pub fn num_v(&self) -> usize {
+        self.graph.num_v()
+    }
+
+    /// Returns the number of edges, double-counting undirected edges.
+    #[must_use]
+    pub fn num_e(&self) -> usize {
+        self.graph.num_e()
+    }
+
+    /// Returns the payload of edge e.
+    #[must_use]
+    pub fn weight(&self, e: usize) -> &W {
+        &self.weight[e]
+    }
+
+    /// Adds a directed edge from `u` to `v` with weight `w`.
+    pub fn add_edge(&mut self, u: usize, v: usize, w: W) {
+        self.graph.add_edge(u, v);
+        self.weight.push(w);
+    }
+
+    /// Gets vertex u's adjacency list.
+    #[must_use]
+    pub fn adj_list(&self, u: usize) -> WeightedAdjListIterator<'_, W> {
+        WeightedAdjListIterator {
+            adj: self.graph.adj_list(u),
+            weight: &self.weight,
+        }
+    }
+}
+
+impl<W: Clone> WeightedGraph<W> {
+    /// An undirected edge is two directed edges with the same weight. If edges
+    /// are added only via this funcion, the reverse of any edge e can be found
+    /// at e^1.
+    pub fn add_undirected_edge(&mut self, u: usize, v: usize, w: W) {
+        self.add_edge(u, v, w.clone());
+        self.add_edge(v, u, w);
+    }
+}
+
+/// An iterator for convenient weighted adjacency list traversal.
+pub struct WeightedAdjListIterator<'a, W> {
+    adj: AdjListIterator<'a>,
+    weight: &'a [W],
+}
+
+impl<'a, W> Iterator for WeightedAdjListIterator<'a, W> {
+    type Item = (usize, usize, &'a W);
+
+    /// Produces an outgoing edge, vertex and weight.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.adj.next().map(|(e, v)| (e, v, &self.weight[e]))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -110,4 +202,23 @@ mod test {
             assert_eq!(v, graph.endp[e]);
         }
     }
+
+    #[test]
+    fn test_weighted_adj_list() {
+        let mut graph = WeightedGraph::new(4, 6);
+        graph.add_undirected_edge(1, 2, -3_i64);
+        graph.add_undirected_edge(3, 1, 7);
+        graph.add_edge(0, 1, 5);
+
+        let adj = graph.adj_list(1).collect::<Vec<_>>();
+
+        assert_eq!(adj, vec![(3, 3, &7), (0, 2, &-3)]);
+        for (e, v, &w) in adj {
+            assert_eq!(graph.graph().endp[e ^ 1], 1);
+            assert_eq!(graph.graph().endp[e], v);
+            assert_eq!(*graph.weight(e ^ 1), w);
+        }
+        assert_eq!(graph.num_e(), 5);
+        assert!(graph.graph().adj_list(0).eq([(4, 1)]));
+    }
 }
@@ -0,0 +1,172 @@
+//! Strongly connected components of a directed graph.
+
+use std::cmp::min;
+
+use super::Graph;
+
+/// Iterative Tarjan's algorithm in `O(n + m)`. Returns the number of strongly
+/// connected components and the component id of every vertex. Ids are in
+/// topological order: every edge goes from a component to the same or a later one.
+#[must_use]
+pub fn strongly_connected_components(graph: &Graph) -> (usize, Vec<usize>) {
+    const UNVISITED: usize = usize::MAX;
+    let n = graph.num_v();
+    let mut index = vec![UNVISITED; n];
+    let mut low = vec![0; n];
+    let mut component = vec![UNVISITED; n];
+    let mut count = 0;
+    let mut timer = 0;
+    // Visited vertices, which aren't assigned to a component yet
+    let mut stack = Vec::new();
+    // Vertices on the current DFS path with their remaining edges
+    let mut path = Vec::new();
+    for root in 0..n {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        index[root] = timer;
+        low[root] = timer;
+        timer += 1;
+        stack.push(root);
+        path.push((root, graph.adj_list(root)));
+        while let Some((u, edges)) = path.last_mut() {
+            let u = *u;
+            if let Some((_, v)) = edges.next() {
+                if index[v] == UNVISITED {
+                    index[v] = timer;
+                    low[v] = timer;
+                    timer += 1;
+                    stack.push(v);
+                    path.push((v, graph.adj_list(v)));
+                } else if component[v] == UNVISITED {
+                    low[u] = min(low[u], index[v]);
+                }
+                continue;
+            }
+            path.pop();
+            if let Some(&(parent, _)) = path.last() {
+                low[parent] = min(low[parent], low[u]);
+            }
+            if low[u] == index[u] {
+                while let Some(v) = stack.pop() {
+                    component[v] = count;
+                    if v == u {
+                        break;
+                    }
+                }
+                count += 1;
+            }
+        }
+    }
+    // Tarjan's algorithm finds the components in reverse topological order
+    for id in &mut component {
+        *id = count - 1 - *id;
+    }
+    (count, component)
+}
+
+/// Returns the condensation DAG: a vertex for every component and an edge between
+/// components for every set of edges between their vertices.
+/// `count` and `component` are the result of [`strongly_connected_components`].
+#[must_use]
+pub fn condensation(graph: &Graph, count: usize, component: &[usize]) -> Graph {
+    let mut edges = Vec::new();
+    for u in 0..graph.num_v() {
+        for (_, v) in graph.adj_list(u) {
+            if component[u] != component[v] {
+                edges.push((component[u], component[v]));
+            }
+        }
+    }
+    edges.sort_unstable();
+    edges.dedup();
+    let mut dag = Graph::new(count, edges.len());
+    for (u, v) in edges {
+        dag.add_edge(u, v);
+    }
+    dag
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::traversal::bfs;
+
+    use proptest::prelude::*;
+
+    fn build(n: usize, edges: &[(usize, usize)]) -> Graph {
+        let mut graph = Graph::new(n, edges.len());
+        for &(u, v) in edges {
+            graph.add_edge(u % n, v % n);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_basic() {
+        // {1, 2, 3} -> {0, 4} -> {5}
+        let edges = [
+            (1, 2),
+            (2, 3),
+            (3, 1),
+            (3, 0),
+            (2, 4),
+            (0, 4),
+            (4, 0),
+            (4, 5),
+        ];
+        let graph = build(6, &edges);
+        let (count, component) = strongly_connected_components(&graph);
+        assert_eq!(
+            (count, component.as_slice()),
+            (3, [1, 0, 0, 0, 1, 2].as_slice())
+        );
+        let dag = condensation(&graph, count, &component);
+        assert_eq!(dag.num_e(), 2);
+        assert!(dag.adj_list(0).eq([(0, 1)]));
+        assert!(dag.adj_list(1).eq([(1, 2)]));
+
+        // A cycle long enough to overflow the stack of a recursive DFS
+        let n = 200_000;
+        let edges: Vec<_> = (0..n).map(|u| (u, (u + 1) % n)).collect();
+        let (count, component) = strongly_connected_components(&build(n, &edges));
+        assert_eq!(count, 1);
+        assert!(component.iter().all(|&id| id == 0));
+    }
+
+    proptest! {
+        #[test]
+        fn matches_reachability(
+            n in 1..12_usize,
+            edges in prop::collection::vec((0..12_usize, 0..12_usize), 0..30),
+        ) {
+            let graph = build(n, &edges);
+            let (count, component) = strongly_connected_components(&graph);
+            let reachable: Vec<_> = (0..n).map(|u| bfs(&graph, u)).collect();
+            for u in 0..n {
+                for v in 0..n {
+                    let mutual = reachable[u][v].is_some() && reachable[v][u].is_some();
+                    prop_assert_eq!(component[u] == component[v], mutual);
+                }
+                for (_, v) in graph.adj_list(u) {
+                    prop_assert!(component[u] <= component[v]);
+                }
+            }
+            prop_assert_eq!(count, component.iter().max().unwrap() + 1);
+
+            let dag = condensation(&graph, count, &component);
+            let mut expected: Vec<_> = edges
+                .iter()
+                .map(|&(u, v)| (component[u % n], component[v % n]))
+                .filter(|(cu, cv)| cu != cv)
+                .collect();
+            expected.sort_unstable();
+            expected.dedup();
+            let mut actual: Vec<_> = (0..count)
+                .flat_map(|cu| dag.adj_list(cu).map(move |(_, cv)| (cu, cv)))
+                .collect();
+            actual.sort_unstable();
+            prop_assert_eq!(actual, expected);
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! Single-source and all-pairs shortest paths. Distances are `None` for
+//! unreachable vertices, `W::default()` is assumed to be zero.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+    ops::Add,
+};
+
+use super::{Graph, WeightedGraph};
+use crate::vec::Vec2;
+
+/// Shortest paths from a single source.
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<W> {
+    pub dist: Vec<Option<W>>,
+    /// Previous vertex on a shortest path, `None` for the source and unreachable
+    /// vertices.
+    pub pred: Vec<Option<usize>>,
+}
+
+impl<W> ShortestPaths<W> {
+    fn new(n: usize, source: usize, zero: W) -> Self
+    where
+        W: Clone,
+    {
+        let mut dist = vec![None; n];
+        dist[source] = Some(zero);
+        Self {
+            dist,
+            pred: vec![None; n],
+        }
+    }
+
+    /// Returns the vertices of a shortest path from the source to `target`, or
+    /// `None` if `target` is unreachable.
+    #[must_use]
+    pub fn path(&self, target: usize) -> Option<Vec<usize>> {
+        self.dist[target].as_ref()?;
+        let mut path = vec![target];
+        let mut u = target;
+        while let Some(prev) = self.pred[u] {
+            path.push(prev);
+            u = prev;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Dijkstra's algorithm with a binary heap in `O(m log n)`. All weights must be
+/// non-negative.
+#[must_use]
+pub fn dijkstra<W>(graph: &WeightedGraph<W>, source: usize) -> ShortestPaths<W>
+where
+    W: Copy + Ord + Default + Add<Output = W>,
+{
+    let mut paths = ShortestPaths::new(graph.num_v(), source, W::default());
+    let mut heap = BinaryHeap::from([Reverse((W::default(), source))]);
+    while let Some(Reverse((dist_u, u))) = heap.pop() {
+        if paths.dist[u] != Some(dist_u) {
+            continue;
+        }
+        for (_, v, &w) in graph.adj_list(u) {
+            let dist_v = dist_u + w;
+            if paths.dist[v].is_none_or(|old| dist_v < old) {
+                paths.dist[v] = Some(dist_v);
+                paths.pred[v] = Some(u);
+                heap.push(Reverse((dist_v, v)));
+            }
+        }
+    }
+    paths
+}
+
+/// 0-1 BFS in `O(n + m)`. Every weight must be either zero or the same positive
+/// value (usually `1`).
+#[must_use]
+pub fn zero_one_bfs<W>(graph: &WeightedGraph<W>, source: usize) -> ShortestPaths<W>
+where
+    W: Copy + Ord + Default + Add<Output = W>,
+{
+    let mut paths = ShortestPaths::new(graph.num_v(), source, W::default());
+    let mut deque = VecDeque::from([(W::default(), source)]);
+    while let Some((dist_u, u)) = deque.pop_front() {
+        if paths.dist[u] != Some(dist_u) {
+            continue;
+        }
+        for (_, v, &w) in graph.adj_list(u) {
+            let dist_v = dist_u + w;
+            if paths.dist[v].is_none_or(|old| dist_v < old) {
+                paths.dist[v] = Some(dist_v);
+                paths.pred[v] = Some(u);
+                if w == W::default() {
+                    deque.push_front((dist_v, v));
+                } else {
+                    deque.push_back((dist_v, v));
+                }
+            }
+        }
+    }
+    paths
+}
+
+/// Returned by [`bellman_ford`], if a negative cycle is reachable from the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegativeCycle {
+    /// Vertices with arbitrarily short paths from the source, in increasing order.
+    pub affected: Vec<usize>,
+}
+
+/// Bellman-Ford algorithm in `O(nm)`, stops early once the distances settle.
+/// Weights may be negative.
+///
+/// # Errors
+///
+/// Returns [`NegativeCycle`] if a negative cycle is reachable from `source`.
+pub fn bellman_ford<W>(
+    graph: &WeightedGraph<W>,
+    source: usize,
+) -> Result<ShortestPaths<W>, NegativeCycle>
+where
+    W: Copy + Ord + Default + Add<Output = W>,
+{
+    let n = graph.num_v();
+    let mut paths = ShortestPaths::new(n, source, W::default());
+    // Without negative cycles every shortest path has at most `n - 1` edges, so
+    // the vertices still relaxed in round `n` are reachable from such a cycle
+    let mut relaxed = Vec::new();
+    for _ in 0..n {
+        relaxed.clear();
+        for u in 0..n {
+            let Some(dist_u) = paths.dist[u] else {
+                continue;
+            };
+            for (_, v, &w) in graph.adj_list(u) {
+                let dist_v = dist_u + w;
+                if paths.dist[v].is_none_or(|old| dist_v < old) {
+                    paths.dist[v] = Some(dist_v);
+                    paths.pred[v] = Some(u);
+                    relaxed.push(v);
+                }
+            }
+        }
+        if relaxed.is_empty() {
+            return Ok(paths);
+        }
+    }
+    Err(NegativeCycle {
+        affected: reachable(graph.graph(), relaxed),
+    })
+}
+
+/// Returns the sorted vertices reachable from `sources`.
+fn reachable(graph: &Graph, sources: Vec<usize>) -> Vec<usize> {
+    let mut visited = vec![false; graph.num_v()];
+    for &u in &sources {
+        visited[u] = true;
+    }
+    let mut stack = sources;
+    while let Some(u) = stack.pop() {
+        for (_, v) in graph.adj_list(u) {
+            if !visited[v] {
+                visited[v] = true;
+                stack.push(v);
+            }
+        }
+    }
+    (0..graph.num_v()).filter(|&u| visited[u]).collect()
+}
+
+/// Floyd-Warshall algorithm in `O(n^3)`. Replaces the matrix of edge weights
+/// (`None` for no edge) with the matrix of distances. The diagonal should be
+/// `Some(zero)`.
+///
+/// Weights may be negative. After the call vertex `i` lies on a negative cycle
+/// iff `dist[(i, i)] < Some(zero)`.
+///
+/// # Panics
+///
+/// Panics if `dist` is not square.
+pub fn floyd_warshall<W>(dist: &mut Vec2<Option<W>>)
+where
+    W: Copy + Ord + Add<Output = W>,
+{
+    let (n, m) = dist.dims();
+    assert_eq!(n, m, "the matrix must be square");
+    for k in 0..n {
+        for i in 0..n {
+            let Some(dist_ik) = dist[(i, k)] else {
+                continue;
+            };
+            for j in 0..n {
+                if let Some(dist_kj) = dist[(k, j)] {
+                    let through_k = dist_ik + dist_kj;
+                    if dist[(i, j)].is_none_or(|old| through_k < old) {
+                        dist[(i, j)] = Some(through_k);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    type Int = i64;
+
+    fn build(n: usize, edges: &[(usize, usize, Int)]) -> WeightedGraph<Int> {
+        let mut graph = WeightedGraph::new(n, edges.len());
+        for &(u, v, w) in edges {
+            graph.add_edge(u % n, v % n, w);
+        }
+        graph
+    }
+
+    fn matrix(graph: &WeightedGraph<Int>) -> Vec2<Option<Int>> {
+        let n = graph.num_v();
+        let mut dist = Vec2::new((n, n));
+        for u in 0..n {
+            dist[(u, u)] = Some(0);
+            for (_, v, &w) in graph.adj_list(u) {
+                if dist[(u, v)].is_none_or(|old| w < old) {
+                    dist[(u, v)] = Some(w);
+                }
+            }
+        }
+        dist
+    }
+
+    /// Checks that the predecessors form shortest paths.
+    fn check_paths(graph: &WeightedGraph<Int>, paths: &ShortestPaths<Int>) {
+        let w = matrix(graph);
+        for target in 0..graph.num_v() {
+            let Some(path) = paths.path(target) else {
+                assert_eq!(paths.dist[target], None);
+                continue;
+            };
+            let len: Int = path.windows(2).map(|e| w[(e[0], e[1])].unwrap()).sum();
+            assert_eq!(Some(len), paths.dist[target]);
+        }
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let graph = build(5, &[(0, 1, 4), (0, 2, 1), (2, 1, 2), (1, 3, 5), (2, 3, 8)]);
+        let paths = dijkstra(&graph, 0);
+        assert_eq!(paths.dist, [Some(0), Some(3), Some(1), Some(8), None]);
+        assert_eq!(paths.path(3), Some(vec![0, 2, 1, 3]));
+        assert_eq!(paths.path(0), Some(vec![0]));
+        assert_eq!(paths.path(4), None);
+    }
+
+    #[test]
+    fn test_negative_cycle() {
+        // 1 -> 2 -> 3 -> 1 has weight -1, 4 is only reachable from the cycle
+        let edges = [
+            (0, 1, 1),
+            (1, 2, 1),
+            (2, 3, 1),
+            (3, 1, -3),
+            (3, 4, 0),
+            (0, 5, 2),
+        ];
+        let graph = build(7, &edges);
+        assert_eq!(
+            bellman_ford(&graph, 0).unwrap_err(),
+            NegativeCycle {
+                affected: vec![1, 2, 3, 4]
+            }
+        );
+        let paths = bellman_ford(&graph, 5).unwrap();
+        assert_eq!(paths.dist[5], Some(0));
+        assert!(
+            paths
+                .dist
+                .iter()
+                .enumerate()
+                .all(|(u, d)| u == 5 || d.is_none())
+        );
+
+        let mut dist = matrix(&graph);
+        floyd_warshall(&mut dist);
+        assert!((0..7).all(|u| (dist[(u, u)] < Some(0)) == (1..=3).contains(&u)));
+    }
+
+    proptest! {
+        #[test]
+        fn non_negative_matches_floyd_warshall(
+            n in 1..10_usize,
+            edges in prop::collection::vec((0..10_usize, 0..10_usize, 0..=1 as Int), 0..30),
+            scale in 1..=20 as Int,
+        ) {
+            let graph = build(n, &edges);
+            let mut dist = matrix(&graph);
+            floyd_warshall(&mut dist);
+            let scaled: Vec<_> = edges.iter().map(|&(u, v, w)| (u, v, w * scale)).collect();
+            let scaled_graph = build(n, &scaled);
+            for source in 0..n {
+                let expected: Vec<_> = (0..n).map(|v| dist[(source, v)]).collect();
+                let paths = zero_one_bfs(&graph, source);
+                prop_assert_eq!(&paths.dist, &expected);
+                check_paths(&graph, &paths);
+                let paths = bellman_ford(&graph, source).unwrap();
+                prop_assert_eq!(&paths.dist, &expected);
+                check_paths(&graph, &paths);
+
+                let paths = dijkstra(&scaled_graph, source);
+                let expected: Vec<_> = expected.iter().map(|d| d.map(|d| d * scale)).collect();
+                prop_assert_eq!(&paths.dist, &expected);
+                check_paths(&scaled_graph, &paths);
+                let paths = zero_one_bfs(&scaled_graph, source);
+                prop_assert_eq!(&paths.dist, &expected);
+            }
+        }
+
+        #[test]
+        fn bellman_ford_matches_floyd_warshall(
+            n in 1..8_usize,
+            edges in prop::collection::vec((0..8_usize, 0..8_usize, -5..=20 as Int), 0..20),
+        ) {
+            let graph = build(n, &edges);
+            let mut dist = matrix(&graph);
+            floyd_warshall(&mut dist);
+            for source in 0..n {
+                let affected: Vec<usize> = (0..n)
+                    .filter(|&v| {
+                        (0..n).any(|k| {
+                            dist[(source, k)].is_some()
+                                && dist[(k, k)] < Some(0)
+                                && dist[(k, v)].is_some()
+                        })
+                    })
+                    .collect();
+                match bellman_ford(&graph, source) {
+                    Ok(paths) => {
+                        prop_assert!(affected.is_empty());
+                        let expected: Vec<_> = (0..n).map(|v| dist[(source, v)]).collect();
+                        prop_assert_eq!(&paths.dist, &expected);
+                        check_paths(&graph, &paths);
+                    }
+                    Err(cycle) => prop_assert_eq!(cycle.affected, affected),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Iterative traversals, which don't overflow the stack on deep graphs.
+
+use std::collections::VecDeque;
+
+use super::{AdjListIterator, Graph};
+
+/// Returns the number of edges on a shortest path from `source` to every vertex,
+/// or `None` for unreachable vertices.
+#[must_use]
+pub fn bfs(graph: &Graph, source: usize) -> Vec<Option<usize>> {
+    let mut dist = vec![None; graph.num_v()];
+    dist[source] = Some(0);
+    let mut queue = VecDeque::from([(source, 0)]);
+    while let Some((u, dist_u)) = queue.pop_front() {
+        for (_, v) in graph.adj_list(u) {
+            if dist[v].is_none() {
+                dist[v] = Some(dist_u + 1);
+                queue.push_back((v, dist_u + 1));
+            }
+        }
+    }
+    dist
+}
+
+/// Vertices reachable from the root of a DFS, in the order they are entered and
+/// left. Vertices are visited in the same order as by the recursive DFS.
+#[derive(Debug, Clone)]
+pub struct DfsOrder {
+    pub preorder: Vec<usize>,
+    pub postorder: Vec<usize>,
+    /// Parent in the DFS tree, `None` for the root and unreachable vertices.
+    pub parent: Vec<Option<usize>>,
+}
+
+#[must_use]
+pub fn dfs(graph: &Graph, root: usize) -> DfsOrder {
+    let n = graph.num_v();
+    let mut order = DfsOrder {
+        preorder: Vec::with_capacity(n),
+        postorder: Vec::with_capacity(n),
+        parent: vec![None; n],
+    };
+    let mut visited = vec![false; n];
+    visited[root] = true;
+    order.preorder.push(root);
+    // Vertices on the current path with their remaining edges
+    let mut stack: Vec<(usize, AdjListIterator)> = vec![(root, graph.adj_list(root))];
+    while let Some((u, edges)) = stack.last_mut() {
+        let u = *u;
+        if let Some((_, v)) = edges.find(|&(_, v)| !visited[v]) {
+            visited[v] = true;
+            order.parent[v] = Some(u);
+            order.preorder.push(v);
+            stack.push((v, graph.adj_list(v)));
+        } else {
+            order.postorder.push(u);
+            stack.pop();
+        }
+    }
+    order
+}
+
+/// Returns the number of connected components and the component id of every
+/// vertex. Ids are numbered in the order of their smallest vertices.
+///
+/// The graph must be undirected, i.e. built with [`Graph::add_undirected_edge`].
+#[must_use]
+pub fn connected_components(graph: &Graph) -> (usize, Vec<usize>) {
+    let n = graph.num_v();
+    let mut component = vec![usize::MAX; n];
+    let mut count = 0;
+    let mut stack = Vec::new();
+    for root in 0..n {
+        if component[root] != usize::MAX {
+            continue;
+        }
+        component[root] = count;
+        stack.push(root);
+        while let Some(u) = stack.pop() {
+            for (_, v) in graph.adj_list(u) {
+                if component[v] == usize::MAX {
+                    component[v] = count;
+                    stack.push(v);
+                }
+            }
+        }
+        count += 1;
+    }
+    (count, component)
+}
+
+/// Returns the vertices ordered so that every edge goes forward (Kahn's
+/// algorithm), or `None` if the graph has a cycle.
+#[must_use]
+pub fn topological_sort(graph: &Graph) -> Option<Vec<usize>> {
+    let n = graph.num_v();
+    let mut in_degree = vec![0_usize; n];
+    for u in 0..n {
+        for (_, v) in graph.adj_list(u) {
+            in_degree[v] += 1;
+        }
+    }
+    let mut order: Vec<usize> = (0..n).filter(|&u| in_degree[u] == 0).collect();
+    // `order[..head]` have been processed, `order[head..]` is the queue
+    let mut head = 0;
+    while head < order.len() {
+        let u = order[head];
+        head += 1;
+        for (_, v) in graph.adj_list(u) {
+            in_degree[v] -= 1;
+            if in_degree[v] == 0 {
+                order.push(v);
+            }
+        }
+    }
+    (order.len() == n).then_some(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    /// Recursive DFS as the reference.
+    fn dfs_recursive(
+        graph: &Graph,
+        u: usize,
+        visited: &mut [bool],
+        order: &mut DfsOrder,
+    ) {
+        order.preorder.push(u);
+        for (_, v) in graph.adj_list(u) {
+            if !visited[v] {
+                visited[v] = true;
+                order.parent[v] = Some(u);
+                dfs_recursive(graph, v, visited, order);
+            }
+        }
+        order.postorder.push(u);
+    }
+
+    fn build(n: usize, edges: &[(usize, usize)], undirected: bool) -> Graph {
+        let mut graph = Graph::new(n, 2 * edges.len());
+        for &(u, v) in edges {
+            if undirected {
+                graph.add_undirected_edge(u % n, v % n);
+            } else {
+                graph.add_edge(u % n, v % n);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn test_bfs() {
+        let graph = build(6, &[(0, 1), (1, 2), (0, 3), (3, 2), (2, 4)], false);
+        assert_eq!(
+            bfs(&graph, 0),
+            [Some(0), Some(1), Some(2), Some(1), Some(3), None]
+        );
+        assert_eq!(
+            bfs(&graph, 3),
+            [None, None, Some(1), Some(0), Some(2), None]
+        );
+    }
+
+    #[test]
+    fn test_dfs() {
+        let graph = build(6, &[(0, 1), (1, 2), (0, 3), (2, 4)], true);
+        let order = dfs(&graph, 0);
+        assert_eq!(order.preorder, [0, 3, 1, 2, 4]);
+        assert_eq!(order.postorder, [3, 4, 2, 1, 0]);
+        assert_eq!(
+            order.parent,
+            [None, Some(0), Some(1), Some(0), Some(2), None]
+        );
+
+        // A path long enough to overflow the stack of a recursive DFS
+        let n = 1_000_000;
+        let edges: Vec<_> = (1..n).map(|v| (v - 1, v)).collect();
+        let order = dfs(&build(n, &edges, false), 0);
+        assert!(order.postorder.iter().copied().eq((0..n).rev()));
+    }
+
+    #[test]
+    fn test_components_and_topological_sort() {
+        let graph = build(7, &[(0, 4), (5, 1), (4, 6), (2, 5)], true);
+        assert_eq!(connected_components(&graph), (3, vec![0, 1, 1, 2, 0, 1, 0]));
+
+        let graph = build(5, &[(3, 1), (1, 0), (4, 0), (3, 2)], false);
+        assert_eq!(topological_sort(&graph), Some(vec![3, 4, 2, 1, 0]));
+        let graph = build(4, &[(0, 1), (1, 2), (2, 3), (3, 1)], false);
+        assert_eq!(topological_sort(&graph), None);
+    }
+
+    proptest! {
+        #[test]
+        fn matches_naive(
+            n in 1..12_usize,
+            edges in prop::collection::vec((0..12_usize, 0..12_usize), 0..25),
+            undirected: bool,
+        ) {
+            let graph = build(n, &edges, undirected);
+
+            let mut expected = DfsOrder {
+                preorder: Vec::new(),
+                postorder: Vec::new(),
+                parent: vec![None; n],
+            };
+            let mut visited = vec![false; n];
+            visited[0] = true;
+            dfs_recursive(&graph, 0, &mut visited, &mut expected);
+            let order = dfs(&graph, 0);
+            prop_assert_eq!(&order.preorder, &expected.preorder);
+            prop_assert_eq!(&order.postorder, &expected.postorder);
+            prop_assert_eq!(&order.parent, &expected.parent);
+
+            // Bellman-Ford style relaxation until nothing changes
+            let mut dist = vec![None; n];
+            dist[0] = Some(0);
+            for _ in 0..n {
+                for u in 0..n {
+                    for (_, v) in graph.adj_list(u) {
+                        if let Some(d) = dist[u] && dist[v].is_none_or(|dv| d + 1 < dv) {
+                            dist[v] = Some(d + 1);
+                        }
+                    }
+                }
+            }
+            prop_assert_eq!(bfs(&graph, 0), dist);
+
+            if undirected {
+                let (count, component) = connected_components(&graph);
+                prop_assert_eq!(count, component.iter().max().unwrap() + 1);
+                let reachable = bfs(&graph, 0);
+                for v in 0..n {
+                    prop_assert_eq!(component[v] == component[0], reachable[v].is_some());
+                }
+            } else if let Some(order) = topological_sort(&graph) {
+                let mut position = vec![0; n];
+                for (i, &u) in order.iter().enumerate() {
+                    position[u] = i;
+                }
+                for u in 0..n {
+                    prop_assert!(graph.adj_list(u).all(|(_, v)| position[u] < position[v]));
+                }
+            } else {
+                // Some vertex must lie on a cycle: it reaches itself
+                let has_cycle = (0..n).any(|u| {
+                    graph.adj_list(u).any(|(_, v)| bfs(&graph, v)[u].is_some())
+                });
+                prop_assert!(has_cycle);
+            }
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Rooted trees: ancestors, subtrees and LCA.
+
+use super::{Graph, traversal::dfs};
+use crate::math::log2;
+
+/// Tree, built from an undirected [`Graph`] and a root, with binary lifting for
+/// ancestor queries.
+///
+/// [`RootedTree::tin`] numbers the vertices in DFS preorder, so the subtree of `v`
+/// is exactly the vertices `u` with `tin(v) <= tin(u) < tout(v)`.
+///
+/// # Panics
+///
+/// May or may not panic if the graph is not a tree, or a vertex is out of bounds.
+#[derive(Debug, Clone)]
+pub struct RootedTree {
+    parent: Vec<Option<usize>>,
+    depth: Vec<usize>,
+    size: Vec<usize>,
+    tin: Vec<usize>,
+    /// `up[k][v]` is the ancestor of `v` `2^k` levels up, or the root.
+    up: Vec<Vec<usize>>,
+}
+
+impl RootedTree {
+    /// Builds the tree in `O(n log n)` without recursion. The graph must be
+    /// connected, with edges added by [`Graph::add_undirected_edge`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `root` is out of bounds, in particular if the graph is empty.
+    #[must_use]
+    pub fn new(graph: &Graph, root: usize) -> Self {
+        let n = graph.num_v();
+        let order = dfs(graph, root);
+        debug_assert_eq!(order.preorder.len(), n, "the graph is not connected");
+        let mut depth = vec![0; n];
+        let mut tin = vec![0; n];
+        for (i, &v) in order.preorder.iter().enumerate() {
+            tin[v] = i;
+            if let Some(p) = order.parent[v] {
+                depth[v] = depth[p] + 1;
+            }
+        }
+        let mut size = vec![1; n];
+        for &v in &order.postorder {
+            if let Some(p) = order.parent[v] {
+                size[p] += size[v];
+            }
+        }
+        let levels = usize::try_from(log2(n.max(1) as u64)).unwrap_or_default() + 1;
+        let mut up = Vec::with_capacity(levels);
+        up.push(
+            (0..n)
+                .map(|v| order.parent[v].unwrap_or(root))
+                .collect::<Vec<_>>(),
+        );
+        for k in 1..levels {
+            let prev = &up[k - 1];
+            let next = (0..n).map(|v| prev[prev[v]]).collect();
+            up.push(next);
+        }
+        Self {
+            parent: order.parent,
+            depth,
+            size,
+            tin,
+            up,
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Returns the parent of `v`, `None` for the root.
+    #[must_use]
+    pub fn parent(&self, v: usize) -> Option<usize> {
+        self.parent[v]
+    }
+
+    /// Returns the number of edges between `v` and the root.
+    #[must_use]
+    pub fn depth(&self, v: usize) -> usize {
+        self.depth[v]
+    }
+
+    /// Returns the number of vertices in the subtree of `v`.
+    #[must_use]
+    pub fn subtree_size(&self, v: usize) -> usize {
+        self.size[v]
+    }
+
+    /// Returns the position of `v` in the DFS preorder.
+    #[must_use]
+    pub fn tin(&self, v: usize) -> usize {
+        self.tin[v]
+    }
+
+    /// Returns the position after the last vertex of the subtree of `v` in the
+    /// DFS preorder.
+    #[must_use]
+    pub fn tout(&self, v: usize) -> usize {
+        self.tin[v] + self.size[v]
+    }
+
+    /// Returns whether `u` is an ancestor of `v`. Every vertex is an ancestor of
+    /// itself.
+    #[must_use]
+    pub fn is_ancestor(&self, u: usize, v: usize) -> bool {
+        self.tin(u) <= self.tin(v) && self.tout(v) <= self.tout(u)
+    }
+
+    /// Returns the ancestor of `v` `k` levels up, or `None` if `k > depth(v)`.
+    /// Takes `O(log n)`.
+    #[must_use]
+    pub fn kth_ancestor(&self, mut v: usize, k: usize) -> Option<usize> {
+        if k > self.depth[v] {
+            return None;
+        }
+        for (level, up) in self.up.iter().enumerate() {
+            if k >> level & 1 == 1 {
+                v = up[v];
+            }
+        }
+        Some(v)
+    }
+
+    /// Returns the lowest common ancestor of `u` and `v` in `O(log n)`.
+    #[must_use]
+    pub fn lca(&self, mut u: usize, v: usize) -> usize {
+        if self.is_ancestor(u, v) {
+            return u;
+        }
+        for up in self.up.iter().rev() {
+            if !self.is_ancestor(up[u], v) {
+                u = up[u];
+            }
+        }
+        self.up[0][u]
+    }
+
+    /// Returns the number of edges on the path between `u` and `v`.
+    #[must_use]
+    pub fn dist(&self, u: usize, v: usize) -> usize {
+        self.depth[u] + self.depth[v] - 2 * self.depth[self.lca(u, v)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_basic() {
+        //     2
+        //    / \
+        //   0   4
+        //  / \
+        // 1   3
+        let mut graph = Graph::new(5, 8);
+        for (u, v) in [(2, 0), (0, 1), (3, 0), (2, 4)] {
+            graph.add_undirected_edge(u, v);
+        }
+        let tree = RootedTree::new(&graph, 2);
+        assert_eq!(tree.len(), 5);
+        assert_eq!(tree.parent(2), None);
+        assert_eq!(tree.parent(3), Some(0));
+        assert_eq!(tree.depth(1), 2);
+        assert_eq!(tree.subtree_size(0), 3);
+        assert!(tree.is_ancestor(0, 3) && tree.is_ancestor(2, 2));
+        assert!(!tree.is_ancestor(0, 4) && !tree.is_ancestor(3, 0));
+        assert_eq!(tree.lca(1, 3), 0);
+        assert_eq!(tree.lca(1, 4), 2);
+        assert_eq!(tree.lca(0, 1), 0);
+        assert_eq!(tree.dist(3, 4), 3);
+        assert_eq!(tree.kth_ancestor(1, 2), Some(2));
+        assert_eq!(tree.kth_ancestor(1, 3), None);
+
+        let tree = RootedTree::new(&Graph::new(1, 0), 0);
+        assert_eq!((tree.lca(0, 0), tree.dist(0, 0)), (0, 0));
+    }
+
+    proptest! {
+        #[test]
+        fn matches_naive(
+            parents in prop::collection::vec(any::<prop::sample::Index>(), 0..40),
+            root in any::<prop::sample::Index>(),
+        ) {
+            // Vertex `i + 1` hangs below a random earlier vertex
+            let n = parents.len() + 1;
+            let mut graph = Graph::new(n, 2 * n);
+            for (i, p) in parents.iter().enumerate() {
+                graph.add_undirected_edge(p.index(i + 1), i + 1);
+            }
+            let root = root.index(n);
+            let tree = RootedTree::new(&graph, root);
+
+            let path_to_root = |mut v: usize| {
+                let mut path = vec![v];
+                while let Some(p) = tree.parent(v) {
+                    path.push(p);
+                    v = p;
+                }
+                path
+            };
+            for u in 0..n {
+                let up = path_to_root(u);
+                prop_assert_eq!(tree.depth(u), up.len() - 1);
+                prop_assert_eq!(*up.last().unwrap(), root);
+                for k in 0..=n {
+                    prop_assert_eq!(tree.kth_ancestor(u, k), up.get(k).copied());
+                }
+                let in_subtree = (0..n).filter(|&v| path_to_root(v).contains(&u)).count();
+                prop_assert_eq!(tree.subtree_size(u), in_subtree);
+                for v in 0..n {
+                    let other = path_to_root(v);
+                    prop_assert_eq!(tree.is_ancestor(u, v), other.contains(&u));
+                    let i = up.iter().position(|w| other.contains(w)).unwrap();
+                    let j = other.iter().position(|&w| w == up[i]).unwrap();
+                    prop_assert_eq!(tree.lca(u, v), up[i]);
+                    prop_assert_eq!(tree.lca(v, u), up[i]);
+                    prop_assert_eq!(tree.dist(u, v), i + j);
+                }
+            }
+        }
+    }
+}
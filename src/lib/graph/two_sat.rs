@@ -0,0 +1,157 @@
+use super::{Graph, scc::strongly_connected_components};
+
+/// 2-SAT solver. Literals use the encoding of [`Graph::add_two_sat_clause`]:
+/// `2 * x` is variable `x` and `2 * x + 1` is its negation, so `lit ^ 1` negates
+/// a literal. See [`TwoSat::literal`].
+#[derive(Debug, Clone, Default)]
+pub struct TwoSat {
+    num_vars: usize,
+    clauses: Vec<(usize, usize)>,
+}
+
+impl TwoSat {
+    /// Returns a formula over `n` variables with no clauses.
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        Self {
+            num_vars: n,
+            clauses: Vec::new(),
+        }
+    }
+
+    /// Returns the literal `x == value`.
+    #[must_use]
+    pub fn literal(x: usize, value: bool) -> usize {
+        2 * x + usize::from(!value)
+    }
+
+    #[must_use]
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// Adds a new variable and returns its index.
+    pub fn add_var(&mut self) -> usize {
+        self.num_vars += 1;
+        self.num_vars - 1
+    }
+
+    /// Adds the clause `a || b`.
+    pub fn x_or_y(&mut self, a: usize, b: usize) {
+        debug_assert!(a < 2 * self.num_vars && b < 2 * self.num_vars);
+        self.clauses.push((a, b));
+    }
+
+    /// Adds the clause `a -> b`, which is `!a || b`.
+    pub fn implies(&mut self, a: usize, b: usize) {
+        self.x_or_y(a ^ 1, b);
+    }
+
+    /// Requires that at most one of `lits` is true with `O(k)` clauses, using
+    /// `k` auxiliary variables: the `i`-th of them is true iff one of
+    /// `lits[..=i]` is.
+    pub fn at_most_one(&mut self, lits: &[usize]) {
+        let mut prev_any: Option<usize> = None;
+        for &lit in lits {
+            let any = Self::literal(self.add_var(), true);
+            self.implies(lit, any);
+            if let Some(prev_any) = prev_any {
+                self.implies(prev_any, any);
+                self.implies(prev_any, lit ^ 1);
+            }
+            prev_any = Some(any);
+        }
+    }
+
+    /// Returns values of all variables (including the auxiliary ones), which
+    /// satisfy every clause, or `None` if there are none. Takes `O(n + m)`.
+    #[must_use]
+    pub fn solve(&self) -> Option<Vec<bool>> {
+        let mut graph = Graph::new(2 * self.num_vars, 2 * self.clauses.len());
+        for &(a, b) in &self.clauses {
+            graph.add_two_sat_clause(a, b);
+        }
+        let (_, component) = strongly_connected_components(&graph);
+        (0..self.num_vars)
+            .map(|x| {
+                let (pos, neg) = (component[2 * x], component[2 * x + 1]);
+                // A literal, which comes later in the topological order, can't
+                // imply its negation
+                (pos != neg).then_some(pos > neg)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    fn satisfies(clauses: &[(usize, usize)], values: &[bool]) -> bool {
+        let is_true = |lit: usize| values[lit / 2] ^ (lit % 2 == 1);
+        clauses.iter().all(|&(a, b)| is_true(a) || is_true(b))
+    }
+
+    #[test]
+    fn test_basic() {
+        let lit = TwoSat::literal;
+        let mut two_sat = TwoSat::new(3);
+        two_sat.x_or_y(lit(0, true), lit(1, true));
+        two_sat.implies(lit(0, true), lit(2, false));
+        two_sat.implies(lit(1, true), lit(0, true));
+        let values = two_sat.solve().unwrap();
+        assert!(values[0] && !values[2]);
+
+        two_sat.x_or_y(lit(2, true), lit(2, true));
+        assert_eq!(two_sat.solve(), None);
+    }
+
+    #[test]
+    fn test_at_most_one() {
+        let lit = TwoSat::literal;
+        let mut two_sat = TwoSat::new(4);
+        two_sat.at_most_one(&[lit(0, true), lit(1, false), lit(2, true), lit(3, true)]);
+        two_sat.x_or_y(lit(2, true), lit(2, true));
+        let values = two_sat.solve().unwrap();
+        assert_eq!(values[..4], [false, true, true, false]);
+
+        two_sat.x_or_y(lit(0, true), lit(0, true));
+        assert_eq!(two_sat.solve(), None);
+    }
+
+    proptest! {
+        #[test]
+        fn matches_brute_force(
+            n in 1..7_usize,
+            clauses in prop::collection::vec((0..14_usize, 0..14_usize), 0..15),
+            group in prop::collection::vec(0..14_usize, 0..5),
+        ) {
+            let clauses: Vec<_> = clauses.iter().map(|&(a, b)| (a % (2 * n), b % (2 * n))).collect();
+            let group: Vec<_> = group.iter().map(|&lit| lit % (2 * n)).collect();
+            let mut two_sat = TwoSat::new(n);
+            for &(a, b) in &clauses {
+                two_sat.x_or_y(a, b);
+            }
+            two_sat.at_most_one(&group);
+
+            let is_valid = |values: &[bool]| {
+                let true_in_group = group.iter().filter(|&&lit| values[lit / 2] ^ (lit % 2 == 1)).count();
+                satisfies(&clauses, values) && true_in_group <= 1
+            };
+            let expected = (0..1_usize << n).any(|mask| {
+                let values: Vec<bool> = (0..n).map(|x| mask >> x & 1 == 1).collect();
+                is_valid(&values)
+            });
+            match two_sat.solve() {
+                Some(values) => {
+                    prop_assert_eq!(values.len(), two_sat.num_vars());
+                    prop_assert!(is_valid(&values[..n]));
+                    prop_assert!(satisfies(&two_sat.clauses, &values));
+                }
+                None => prop_assert!(!expected),
+            }
+        }
+    }
+}
@@ -10,4 +10,5 @@ pub mod math;
 pub mod misc;
 pub mod num;
 pub mod perm;
+pub mod rng;
 pub mod vec;
@@ -1,3 +1,5 @@
+use crate::ds::fenwick_tree::FenwickTree;
+
 /// Reduces a vector of distinct ints to a permutation.
 #[must_use]
 pub fn reduced_perm(v: &[usize]) -> Vec<usize> {
@@ -20,6 +22,19 @@ pub fn inv_perm(v: &[usize]) -> Vec<usize> {
     inv
 }
 
+/// Counts inversions of a permutation, i.e. pairs `i < j` with `v[i] > v[j]`.
+#[must_use]
+pub fn inversions(v: &[usize]) -> usize {
+    let mut seen = FenwickTree::<usize>::new(v.len());
+    let mut ans = 0;
+    for (i, &elem) in v.iter().enumerate() {
+        // Elements seen so far, that are greater than `elem`
+        ans += i - seen.prefix_sum(elem);
+        seen.add(elem, 1);
+    }
+    ans
+}
+
 /// NOT TESTED
 pub fn inversions_odd(v: &mut [usize]) -> bool {
     let mut inv = inv_perm(v);
@@ -54,6 +69,17 @@ mod tests {
         assert_eq!(reduced_perm(&[18, 3, 14, 17, 0]), [4, 1, 2, 3, 0]);
     }
 
+    #[test]
+    fn inversions() {
+        use super::inversions;
+        assert_eq!(inversions(&[]), 0);
+        assert_eq!(inversions(&[0, 1, 2]), 0);
+        assert_eq!(inversions(&[1, 0]), 1);
+        assert_eq!(inversions(&[2, 1, 0]), 3);
+        assert_eq!(inversions(&[2, 0, 1]), 2);
+        assert_eq!(inversions(&[1, 3, 0, 4, 2]), 4);
+    }
+
     #[test]
     fn inv_perm() {
         use super::inv_perm;
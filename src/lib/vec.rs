@@ -14,6 +14,12 @@ impl<T: Default> Vec2<T> {
 }
 
 impl<T> Vec2<T> {
+    /// Returns `(rows, columns)`.
+    #[must_use]
+    pub fn dims(&self) -> (usize, usize) {
+        self.dims
+    }
+
     #[must_use]
     pub fn get(&self, index: (usize, usize)) -> Option<&T> {
         if !(index.0 < self.dims.0 && index.1 < self.dims.1) {